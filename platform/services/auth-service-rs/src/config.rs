@@ -0,0 +1,145 @@
+//! Environment-backed configuration.
+
+use std::env;
+
+#[derive(Debug, Clone)]
+pub struct Config {
+    pub host: String,
+    pub port: u16,
+    pub rust_log: String,
+    pub bcrypt_cost: u32,
+    pub jwt_secret: String,
+    pub jwt_ttl_seconds: u64,
+    /// TTL for refresh tokens issued alongside an access token, in seconds.
+    pub refresh_ttl_seconds: u64,
+    /// JSON array of `keys::SigningKeyConfig` (`kid`, `algorithm`,
+    /// `private_key_pem`). Empty means HMAC-only (`jwt_secret`) mode.
+    pub jwt_signing_keys: String,
+    /// Which configured signing key new access tokens are signed with.
+    pub jwt_active_kid: String,
+    pub pii_encryption_key: String,
+    pub pii_encryption_key_version: String,
+    pub lockout_max_attempts: u32,
+    pub lockout_duration_ms: u64,
+    pub lockout_window_ms: u64,
+    /// Failures from one IP within `ip_lockout_window_ms` before progressive
+    /// delay kicks in.
+    pub ip_lockout_soft_threshold: u32,
+    /// Delay unit: a failure past the soft threshold waits this times
+    /// `2^(failures - soft_threshold)` (twice this for the first one),
+    /// capped at `ip_lockout_max_delay_ms`.
+    pub ip_lockout_base_delay_ms: u64,
+    pub ip_lockout_max_delay_ms: u64,
+    /// Distinct emails attempted from one IP within the window before it's
+    /// treated as credential stuffing and hard-locked.
+    pub ip_lockout_distinct_email_threshold: u32,
+    pub ip_lockout_duration_ms: u64,
+    pub ip_lockout_window_ms: u64,
+    /// Base URL for the HaveIBeenPwned range API.
+    pub hibp_range_endpoint: String,
+    /// Timeout for the HIBP range lookup, in milliseconds.
+    pub hibp_timeout_ms: u64,
+    /// When `true`, a failed HIBP lookup (timeout, network error, non-200)
+    /// is treated as "not breached" so password hashing still succeeds.
+    /// When `false`, a failed lookup is surfaced as an error instead.
+    pub hibp_fail_open: bool,
+    /// Relying party id for WebAuthn (usually the bare domain, e.g.
+    /// `campreserv.com`); must match the origin's domain.
+    pub webauthn_rp_id: String,
+    /// Relying party display name shown in browser passkey prompts.
+    pub webauthn_rp_name: String,
+    /// Expected `clientDataJSON.origin`, e.g. `https://campreserv.com`.
+    pub webauthn_rp_origin: String,
+    /// How long an email OTP stays valid after `generate`, in seconds.
+    pub email_otp_ttl_seconds: u64,
+    /// Wrong guesses allowed before an email OTP is invalidated outright.
+    pub email_otp_max_attempts: u32,
+}
+
+impl Config {
+    pub fn from_env() -> Result<Self, env::VarError> {
+        Ok(Self {
+            host: env::var("HOST").unwrap_or_else(|_| "0.0.0.0".to_string()),
+            port: env::var("PORT")
+                .ok()
+                .and_then(|p| p.parse().ok())
+                .unwrap_or(8083),
+            rust_log: env::var("RUST_LOG").unwrap_or_else(|_| "info".to_string()),
+            bcrypt_cost: env::var("BCRYPT_COST")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(12),
+            jwt_secret: env::var("JWT_SECRET")?,
+            jwt_ttl_seconds: env::var("JWT_TTL_SECONDS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(3600),
+            refresh_ttl_seconds: env::var("REFRESH_TTL_SECONDS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(30 * 24 * 60 * 60),
+            jwt_signing_keys: env::var("JWT_SIGNING_KEYS").unwrap_or_default(),
+            jwt_active_kid: env::var("JWT_ACTIVE_KID").unwrap_or_default(),
+            pii_encryption_key: env::var("PII_ENCRYPTION_KEY")?,
+            pii_encryption_key_version: env::var("PII_ENCRYPTION_KEY_VERSION")
+                .unwrap_or_else(|_| "v1".to_string()),
+            lockout_max_attempts: env::var("LOCKOUT_MAX_ATTEMPTS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(5),
+            lockout_duration_ms: env::var("LOCKOUT_DURATION_MS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(15 * 60 * 1000),
+            lockout_window_ms: env::var("LOCKOUT_WINDOW_MS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(15 * 60 * 1000),
+            ip_lockout_soft_threshold: env::var("IP_LOCKOUT_SOFT_THRESHOLD")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(10),
+            ip_lockout_base_delay_ms: env::var("IP_LOCKOUT_BASE_DELAY_MS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(1000),
+            ip_lockout_max_delay_ms: env::var("IP_LOCKOUT_MAX_DELAY_MS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(5 * 60 * 1000),
+            ip_lockout_distinct_email_threshold: env::var("IP_LOCKOUT_DISTINCT_EMAIL_THRESHOLD")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(20),
+            ip_lockout_duration_ms: env::var("IP_LOCKOUT_DURATION_MS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(60 * 60 * 1000),
+            ip_lockout_window_ms: env::var("IP_LOCKOUT_WINDOW_MS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(15 * 60 * 1000),
+            hibp_range_endpoint: env::var("HIBP_RANGE_ENDPOINT")
+                .unwrap_or_else(|_| "https://api.pwnedpasswords.com/range".to_string()),
+            hibp_timeout_ms: env::var("HIBP_TIMEOUT_MS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(1500),
+            hibp_fail_open: env::var("HIBP_FAIL_OPEN")
+                .map(|v| v.to_lowercase() != "false")
+                .unwrap_or(true),
+            webauthn_rp_id: env::var("WEBAUTHN_RP_ID").unwrap_or_else(|_| "localhost".to_string()),
+            webauthn_rp_name: env::var("WEBAUTHN_RP_NAME").unwrap_or_else(|_| "Campreserv".to_string()),
+            webauthn_rp_origin: env::var("WEBAUTHN_RP_ORIGIN")
+                .unwrap_or_else(|_| "http://localhost".to_string()),
+            email_otp_ttl_seconds: env::var("EMAIL_OTP_TTL_SECONDS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(10 * 60),
+            email_otp_max_attempts: env::var("EMAIL_OTP_MAX_ATTEMPTS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(5),
+        })
+    }
+}
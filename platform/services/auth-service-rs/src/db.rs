@@ -0,0 +1,29 @@
+//! Database access for user credential lookups.
+//!
+//! Not yet wired into `AppState` — uncomment `db_pool` in `main.rs` once
+//! this service owns a connection pool. Exists so the login flow can call
+//! into it directly once it's plumbed through.
+
+use sqlx::PgPool;
+
+use crate::error::Result;
+
+pub struct UserRecord {
+    pub id: String,
+    pub email: String,
+    pub password_hash: String,
+}
+
+pub async fn find_user_by_email(pool: &PgPool, email: &str) -> Result<Option<UserRecord>> {
+    let row: Option<(String, String, String)> =
+        sqlx::query_as("SELECT id, email, password_hash FROM users WHERE email = $1")
+            .bind(email)
+            .fetch_optional(pool)
+            .await?;
+
+    Ok(row.map(|(id, email, password_hash)| UserRecord {
+        id,
+        email,
+        password_hash,
+    }))
+}
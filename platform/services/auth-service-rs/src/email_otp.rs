@@ -0,0 +1,134 @@
+//! Email one-time-code MFA, for accounts that haven't enrolled a TOTP
+//! authenticator.
+//!
+//! `generate` mints a short numeric code, stores only its bcrypt hash plus
+//! an expiry and attempt counter in `EmailOtpStore` (an in-memory map, the
+//! same shape `lockout::LockoutTracker` and `webauthn::ChallengeStore` use,
+//! with room to swap in the commented-out `db_pool`), and returns the
+//! plaintext code to the caller. The service itself never sends mail; the
+//! caller is responsible for delivering the code and is given it directly
+//! for that purpose. `verify` checks the code against the stored hash with
+//! `bcrypt::verify`'s constant-time comparison, rejects expired codes, and
+//! invalidates the code after too many wrong guesses so it can't be
+//! brute-forced.
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+use crate::error::{AppError, Result};
+
+const CODE_LENGTH: usize = 6;
+
+struct PendingOtp {
+    hash: String,
+    expires_at: Instant,
+    attempts_remaining: u32,
+}
+
+/// Tracks email OTPs issued by `generate` until the matching `verify`
+/// consumes them, they expire, or the attempt budget runs out.
+pub struct EmailOtpStore {
+    pending: Mutex<HashMap<String, PendingOtp>>,
+}
+
+impl EmailOtpStore {
+    pub fn new() -> Self {
+        Self {
+            pending: Mutex::new(HashMap::new()),
+        }
+    }
+}
+
+impl Default for EmailOtpStore {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct EmailOtpConfig {
+    pub ttl_seconds: u64,
+    pub max_attempts: u32,
+    pub bcrypt_cost: u32,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct GenerateEmailOtpRequest {
+    pub email: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct GenerateEmailOtpResponse {
+    pub code: String,
+    pub expires_in_seconds: u64,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct VerifyEmailOtpRequest {
+    pub email: String,
+    pub code: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct VerifyEmailOtpResponse {
+    pub valid: bool,
+}
+
+/// Generate a new code for `email`, replacing any code already pending for
+/// it. Returns the plaintext code; only its hash is kept server-side.
+pub fn generate(store: &EmailOtpStore, config: &EmailOtpConfig, email: &str) -> Result<GenerateEmailOtpResponse> {
+    let mut rng = rand::thread_rng();
+    let code: String = (0..CODE_LENGTH).map(|_| rng.gen_range(0..10).to_string()).collect();
+    let hash = bcrypt::hash(&code, config.bcrypt_cost).map_err(|e| AppError::Crypto(format!("bcrypt hash failed: {e}")))?;
+
+    let mut pending = store.pending.lock().expect("email otp store lock poisoned");
+    pending.insert(
+        email.to_string(),
+        PendingOtp {
+            hash,
+            expires_at: Instant::now() + Duration::from_secs(config.ttl_seconds),
+            attempts_remaining: config.max_attempts,
+        },
+    );
+
+    Ok(GenerateEmailOtpResponse {
+        code,
+        expires_in_seconds: config.ttl_seconds,
+    })
+}
+
+/// Verify `code` against the pending OTP for `email`. A correct code
+/// consumes the entry so it can't be reused; a wrong one consumes one
+/// attempt and, once the budget is exhausted, invalidates the code
+/// entirely so further guesses are rejected outright.
+pub fn verify(store: &EmailOtpStore, email: &str, code: &str) -> Result<VerifyEmailOtpResponse> {
+    let mut pending = store.pending.lock().expect("email otp store lock poisoned");
+
+    let entry = match pending.get_mut(email) {
+        Some(entry) => entry,
+        None => return Ok(VerifyEmailOtpResponse { valid: false }),
+    };
+
+    if entry.expires_at <= Instant::now() {
+        pending.remove(email);
+        return Ok(VerifyEmailOtpResponse { valid: false });
+    }
+
+    let valid = bcrypt::verify(code, &entry.hash).map_err(|e| AppError::Crypto(format!("bcrypt verify failed: {e}")))?;
+
+    if valid {
+        pending.remove(email);
+        return Ok(VerifyEmailOtpResponse { valid: true });
+    }
+
+    entry.attempts_remaining = entry.attempts_remaining.saturating_sub(1);
+    if entry.attempts_remaining == 0 {
+        pending.remove(email);
+    }
+
+    Ok(VerifyEmailOtpResponse { valid: false })
+}
@@ -0,0 +1,110 @@
+//! PII field encryption (AES-256-GCM). Every ciphertext is tagged with the
+//! version of the key it was encrypted under. Only the currently configured
+//! key is held, so `decrypt` rejects ciphertext tagged with any other version.
+
+use aes_gcm::aead::{Aead, AeadCore, KeyInit, OsRng};
+use aes_gcm::{Aes256Gcm, Key, Nonce};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use serde::{Deserialize, Serialize};
+
+use crate::error::{AppError, Result};
+
+#[derive(Clone)]
+pub struct EncryptionConfig {
+    pub key: [u8; 32],
+    pub key_version: String,
+}
+
+impl EncryptionConfig {
+    pub fn from_env(key_hex: &str, key_version: &str) -> Self {
+        let decoded = hex::decode(key_hex).expect("PII_ENCRYPTION_KEY must be 64 hex characters (32 bytes)");
+        let key: [u8; 32] = decoded
+            .try_into()
+            .expect("PII_ENCRYPTION_KEY must decode to exactly 32 bytes");
+
+        Self {
+            key,
+            key_version: key_version.to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct EncryptRequest {
+    pub plaintext: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct EncryptResponse {
+    pub ciphertext: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct DecryptRequest {
+    pub ciphertext: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DecryptResponse {
+    pub plaintext: String,
+    pub key_version: String,
+    pub needs_reencrypt: bool,
+}
+
+/// Encrypt with AES-256-GCM. The ciphertext is `key_version || "." ||
+/// base64(nonce || ciphertext || tag)` so `decrypt` can tell which key to
+/// use without a side-channel lookup.
+pub fn encrypt(plaintext: &str, config: &EncryptionConfig) -> Result<String> {
+    let cipher = Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(&config.key));
+    let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+
+    let ciphertext = cipher
+        .encrypt(&nonce, plaintext.as_bytes())
+        .map_err(|e| AppError::Crypto(format!("encryption failed: {e}")))?;
+
+    let mut payload = Vec::with_capacity(nonce.len() + ciphertext.len());
+    payload.extend_from_slice(&nonce);
+    payload.extend_from_slice(&ciphertext);
+
+    Ok(format!("{}.{}", config.key_version, STANDARD.encode(payload)))
+}
+
+/// Decrypt a value produced by `encrypt`. Returns the plaintext, the key
+/// version the ciphertext was encrypted with, and whether that version
+/// differs from the configured one. A ciphertext tagged with any version
+/// other than the configured one is rejected with an error, so the returned
+/// flag is always `false`.
+pub fn decrypt(ciphertext: &str, config: &EncryptionConfig) -> Result<(String, String, bool)> {
+    let (key_version, payload_b64) = ciphertext
+        .split_once('.')
+        .ok_or_else(|| AppError::Crypto("ciphertext missing key version prefix".to_string()))?;
+
+    if key_version != config.key_version {
+        return Err(AppError::Crypto(format!(
+            "unknown key version: {key_version}"
+        )));
+    }
+
+    let payload = STANDARD
+        .decode(payload_b64)
+        .map_err(|e| AppError::Crypto(format!("invalid base64 ciphertext: {e}")))?;
+
+    if payload.len() < 12 {
+        return Err(AppError::Crypto("ciphertext too short".to_string()));
+    }
+
+    let (nonce_bytes, encrypted) = payload.split_at(12);
+    let nonce = Nonce::from_slice(nonce_bytes);
+    let cipher = Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(&config.key));
+
+    let plaintext = cipher
+        .decrypt(nonce, encrypted)
+        .map_err(|e| AppError::Crypto(format!("decryption failed: {e}")))?;
+
+    let plaintext = String::from_utf8(plaintext)
+        .map_err(|e| AppError::Crypto(format!("decrypted payload is not valid utf-8: {e}")))?;
+
+    let needs_reencrypt = key_version != config.key_version;
+
+    Ok((plaintext, key_version.to_string(), needs_reencrypt))
+}
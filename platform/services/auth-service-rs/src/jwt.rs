@@ -0,0 +1,384 @@
+//! JWT creation and validation. Tokens are signed with the shared HMAC
+//! secret, or, for access tokens when a `KeyManager` is configured, with its
+//! active RS256/ES256 key.
+//!
+//! Access tokens are short-lived and stateless. Refresh tokens are longer-
+//! lived, carry a random `jti`, and are tracked server-side by
+//! [`RefreshTokenTracker`] so they can be rotated on use and revoked. Every
+//! refresh token belongs to a "family" (the chain of tokens descended from
+//! one original login); reusing an already-rotated token is treated as a
+//! theft signal and revokes the whole family.
+
+use std::collections::{HashMap, HashSet};
+use std::sync::Mutex;
+
+use jsonwebtoken::{decode, decode_header, encode, DecodingKey, EncodingKey, Header, Validation};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::error::{AppError, Result};
+use crate::keys::KeyManager;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Claims {
+    pub sub: String,
+    pub email: String,
+    pub token_type: String,
+    pub exp: u64,
+    pub iat: u64,
+    /// Present on refresh tokens; identifies the token within its family
+    /// for rotation and revocation tracking.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub jti: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CreateJwtRequest {
+    pub user_id: String,
+    pub email: String,
+    pub ttl_seconds: Option<u64>,
+    pub token_type: Option<String>,
+    /// When `true`, also mint a paired refresh token alongside the access
+    /// token, starting a new token family.
+    pub issue_refresh: Option<bool>,
+    pub refresh_ttl_seconds: Option<u64>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CreateJwtResponse {
+    pub token: String,
+    pub expires_at: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub refresh_token: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub refresh_expires_at: Option<u64>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ValidateJwtRequest {
+    pub token: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ValidateJwtResponse {
+    pub valid: bool,
+    pub claims: Option<Claims>,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RefreshJwtRequest {
+    pub refresh_token: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RefreshJwtResponse {
+    pub token: String,
+    pub expires_at: u64,
+    pub refresh_token: String,
+    pub refresh_expires_at: u64,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RevokeJwtRequest {
+    pub jti: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RevokeJwtResponse {
+    pub revoked: bool,
+}
+
+/// A freshly-minted access/refresh pair, returned from issuance and
+/// rotation alike.
+pub struct TokenPair {
+    pub access_token: String,
+    pub access_expires_at: u64,
+    pub refresh_token: String,
+    pub refresh_expires_at: u64,
+}
+
+fn now_seconds() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+}
+
+/// Sign an access (or other non-refresh) token. When `key_manager` is
+/// `Some`, the token is signed with its active RS256/ES256 key and the
+/// `kid` is stamped into the header so verifiers can pick the matching
+/// public key; otherwise it falls back to the shared HMAC `secret`.
+pub fn create_jwt(
+    user_id: &str,
+    email: &str,
+    secret: &str,
+    ttl_seconds: u64,
+    token_type: Option<&str>,
+    key_manager: Option<&KeyManager>,
+) -> Result<(String, u64)> {
+    let iat = now_seconds();
+    let exp = iat + ttl_seconds;
+
+    let claims = Claims {
+        sub: user_id.to_string(),
+        email: email.to_string(),
+        token_type: token_type.unwrap_or("access").to_string(),
+        exp,
+        iat,
+        jti: None,
+    };
+
+    let token = match key_manager {
+        Some(key_manager) => {
+            let (kid, algorithm, encoding_key) = key_manager.active_key();
+            let mut header = Header::new(algorithm);
+            header.kid = Some(kid.to_string());
+            encode(&header, &claims, encoding_key)
+        }
+        None => encode(&Header::default(), &claims, &EncodingKey::from_secret(secret.as_bytes())),
+    }
+    .map_err(|e| AppError::Crypto(format!("failed to sign JWT: {e}")))?;
+
+    Ok((token, exp))
+}
+
+/// Validate a token. Tokens signed with a `kid` (RS256/ES256) are verified
+/// against the matching public key from `key_manager`; all other tokens
+/// are verified against the shared HMAC `secret`.
+pub fn validate_jwt(token: &str, secret: &str, key_manager: Option<&KeyManager>) -> Result<Claims> {
+    let header = decode_header(token).map_err(|e| AppError::Unauthorized(format!("invalid token: {e}")))?;
+
+    let data = match header.kid {
+        Some(kid) => {
+            let key_manager = key_manager
+                .ok_or_else(|| AppError::Unauthorized("token has a kid but no signing keys are configured".to_string()))?;
+            let (algorithm, decoding_key) = key_manager
+                .decoding_key(&kid)
+                .ok_or_else(|| AppError::Unauthorized(format!("unknown signing key id: {kid}")))?;
+            decode::<Claims>(token, decoding_key, &Validation::new(algorithm))
+        }
+        None => decode::<Claims>(token, &DecodingKey::from_secret(secret.as_bytes()), &Validation::default()),
+    }
+    .map_err(|e| AppError::Unauthorized(format!("invalid token: {e}")))?;
+
+    Ok(data.claims)
+}
+
+fn create_refresh_token(
+    user_id: &str,
+    email: &str,
+    secret: &str,
+    ttl_seconds: u64,
+    jti: &str,
+) -> Result<(String, u64)> {
+    let iat = now_seconds();
+    let exp = iat + ttl_seconds;
+
+    let claims = Claims {
+        sub: user_id.to_string(),
+        email: email.to_string(),
+        token_type: "refresh".to_string(),
+        exp,
+        iat,
+        jti: Some(jti.to_string()),
+    };
+
+    let token = encode(&Header::default(), &claims, &EncodingKey::from_secret(secret.as_bytes()))
+        .map_err(|e| AppError::Crypto(format!("failed to sign JWT: {e}")))?;
+
+    Ok((token, exp))
+}
+
+fn issue_pair_in_family(
+    user_id: &str,
+    email: &str,
+    secret: &str,
+    access_ttl_seconds: u64,
+    refresh_ttl_seconds: u64,
+    family_id: &str,
+    tracker: &RefreshTokenTracker,
+    key_manager: Option<&KeyManager>,
+) -> Result<TokenPair> {
+    let (access_token, access_expires_at) =
+        create_jwt(user_id, email, secret, access_ttl_seconds, Some("access"), key_manager)?;
+
+    // Refresh tokens stay HMAC-signed and internal to this service, so
+    // rotation/revocation bookkeeping doesn't need to follow the active
+    // signing key around.
+    let jti = Uuid::new_v4().to_string();
+    let (refresh_token, refresh_expires_at) =
+        create_refresh_token(user_id, email, secret, refresh_ttl_seconds, &jti)?;
+    tracker.register(&jti, family_id);
+
+    Ok(TokenPair {
+        access_token,
+        access_expires_at,
+        refresh_token,
+        refresh_expires_at,
+    })
+}
+
+/// Mint a brand-new access/refresh pair, starting a new token family.
+pub fn issue_token_pair(
+    user_id: &str,
+    email: &str,
+    secret: &str,
+    access_ttl_seconds: u64,
+    refresh_ttl_seconds: u64,
+    tracker: &RefreshTokenTracker,
+    key_manager: Option<&KeyManager>,
+) -> Result<TokenPair> {
+    let family_id = Uuid::new_v4().to_string();
+    issue_pair_in_family(
+        user_id,
+        email,
+        secret,
+        access_ttl_seconds,
+        refresh_ttl_seconds,
+        &family_id,
+        tracker,
+        key_manager,
+    )
+}
+
+/// Validate an incoming refresh token and rotate it: the presented `jti`
+/// is consumed and a new access/refresh pair is issued in the same family.
+/// Presenting a `jti` that was already consumed is treated as a theft
+/// signal and revokes the entire family.
+pub fn rotate_refresh_token(
+    refresh_token: &str,
+    secret: &str,
+    access_ttl_seconds: u64,
+    refresh_ttl_seconds: u64,
+    tracker: &RefreshTokenTracker,
+    key_manager: Option<&KeyManager>,
+) -> Result<TokenPair> {
+    let claims = validate_jwt(refresh_token, secret, None)?;
+    if claims.token_type != "refresh" {
+        return Err(AppError::Unauthorized("token is not a refresh token".to_string()));
+    }
+    let jti = claims
+        .jti
+        .as_deref()
+        .ok_or_else(|| AppError::Unauthorized("refresh token is missing a jti".to_string()))?;
+
+    let family_id = tracker.consume(jti)?;
+
+    issue_pair_in_family(
+        &claims.sub,
+        &claims.email,
+        secret,
+        access_ttl_seconds,
+        refresh_ttl_seconds,
+        &family_id,
+        tracker,
+        key_manager,
+    )
+}
+
+#[derive(Debug, Clone)]
+struct RefreshTokenState {
+    family_id: String,
+    consumed: bool,
+}
+
+/// Server-side record of issued refresh-token `jti`s, used to enforce
+/// single-use rotation and revocation.
+///
+/// In-memory for now, keyed the same way `LockoutTracker` keys accounts;
+/// swap the `Mutex<HashMap<..>>` fields for the commented-out `db_pool`
+/// once refresh tokens need to survive a restart.
+pub struct RefreshTokenTracker {
+    tokens: Mutex<HashMap<String, RefreshTokenState>>,
+    revoked_families: Mutex<HashSet<String>>,
+    revoked_jtis: Mutex<HashSet<String>>,
+}
+
+impl RefreshTokenTracker {
+    pub fn new() -> Self {
+        Self {
+            tokens: Mutex::new(HashMap::new()),
+            revoked_families: Mutex::new(HashSet::new()),
+            revoked_jtis: Mutex::new(HashSet::new()),
+        }
+    }
+
+    fn register(&self, jti: &str, family_id: &str) {
+        let mut tokens = self.tokens.lock().expect("refresh token tracker lock poisoned");
+        tokens.insert(
+            jti.to_string(),
+            RefreshTokenState {
+                family_id: family_id.to_string(),
+                consumed: false,
+            },
+        );
+    }
+
+    /// Mark `jti` consumed and return its family, or an error if it is
+    /// unknown, already revoked, or a replay of an already-consumed token
+    /// (in which case the whole family is revoked before returning).
+    fn consume(&self, jti: &str) -> Result<String> {
+        let mut tokens = self.tokens.lock().expect("refresh token tracker lock poisoned");
+        let state = tokens
+            .get(jti)
+            .cloned()
+            .ok_or_else(|| AppError::Unauthorized("unknown or expired refresh token".to_string()))?;
+
+        if self.revoked_jtis.lock().expect("refresh token tracker lock poisoned").contains(jti)
+            || self
+                .revoked_families
+                .lock()
+                .expect("refresh token tracker lock poisoned")
+                .contains(&state.family_id)
+        {
+            return Err(AppError::Unauthorized("refresh token has been revoked".to_string()));
+        }
+
+        if state.consumed {
+            self.revoked_families
+                .lock()
+                .expect("refresh token tracker lock poisoned")
+                .insert(state.family_id.clone());
+            return Err(AppError::Unauthorized(
+                "refresh token reuse detected; token family revoked".to_string(),
+            ));
+        }
+
+        tokens.get_mut(jti).expect("checked above").consumed = true;
+        Ok(state.family_id)
+    }
+
+    /// Blacklist a single `jti`. `rotate_refresh_token` and the validate-jwt
+    /// handler (through `is_revoked`) then reject it; `validate_jwt` in this
+    /// module does not consult the tracker.
+    pub fn revoke_jti(&self, jti: &str) {
+        self.revoked_jtis
+            .lock()
+            .expect("refresh token tracker lock poisoned")
+            .insert(jti.to_string());
+    }
+
+    /// Whether `jti` has been individually blacklisted or belongs to a
+    /// family revoked by reuse detection.
+    pub fn is_revoked(&self, jti: &str) -> bool {
+        if self.revoked_jtis.lock().expect("refresh token tracker lock poisoned").contains(jti) {
+            return true;
+        }
+        let tokens = self.tokens.lock().expect("refresh token tracker lock poisoned");
+        match tokens.get(jti) {
+            Some(state) => self
+                .revoked_families
+                .lock()
+                .expect("refresh token tracker lock poisoned")
+                .contains(&state.family_id),
+            None => false,
+        }
+    }
+}
+
+impl Default for RefreshTokenTracker {
+    fn default() -> Self {
+        Self::new()
+    }
+}
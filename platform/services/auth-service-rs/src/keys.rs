@@ -0,0 +1,178 @@
+//! Asymmetric JWT signing key management and JWKS publication.
+//!
+//! Loads one or more RSA/EC private keys (PEM, from config/env), keeps the
+//! `EncodingKey` for signing in memory, and derives the public half for
+//! verification and JWKS publication. The private key material itself is
+//! never serialized or returned from this module. Holding multiple keys
+//! lets operators add a new signing key, roll `jwt_active_kid` over to it,
+//! and drop the old key once every outstanding token has expired, without
+//! downtime.
+
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use jsonwebtoken::{Algorithm, DecodingKey, EncodingKey};
+use p256::elliptic_curve::sec1::ToEncodedPoint;
+use rsa::traits::PublicKeyParts;
+use serde::{Deserialize, Serialize};
+
+use crate::error::{AppError, Result};
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct SigningKeyConfig {
+    pub kid: String,
+    pub algorithm: String,
+    pub private_key_pem: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Jwk {
+    pub kty: String,
+    #[serde(rename = "use")]
+    pub use_: String,
+    pub kid: String,
+    pub alg: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub n: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub e: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub crv: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub x: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub y: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct JwksResponse {
+    pub keys: Vec<Jwk>,
+}
+
+struct SigningKey {
+    algorithm: Algorithm,
+    encoding_key: EncodingKey,
+    decoding_key: DecodingKey,
+    jwk: Jwk,
+}
+
+/// Holds every active signing key, keyed by `kid`, plus which one new
+/// tokens should be signed with.
+pub struct KeyManager {
+    keys: std::collections::HashMap<String, SigningKey>,
+    active_kid: String,
+}
+
+impl KeyManager {
+    pub fn from_configs(configs: &[SigningKeyConfig], active_kid: &str) -> Result<Self> {
+        let mut keys = std::collections::HashMap::new();
+        for cfg in configs {
+            let key = build_signing_key(cfg)?;
+            keys.insert(cfg.kid.clone(), key);
+        }
+        if !keys.contains_key(active_kid) {
+            return Err(AppError::Internal(format!(
+                "JWT_ACTIVE_KID {active_kid} does not match any configured signing key"
+            )));
+        }
+        Ok(Self {
+            keys,
+            active_kid: active_kid.to_string(),
+        })
+    }
+
+    /// The `(kid, algorithm, encoding key)` new access tokens should be signed with.
+    pub fn active_key(&self) -> (&str, Algorithm, &EncodingKey) {
+        let key = self.keys.get(&self.active_kid).expect("active_kid always present");
+        (&self.active_kid, key.algorithm, &key.encoding_key)
+    }
+
+    /// Look up the decoding key and expected algorithm for a token's `kid`.
+    pub fn decoding_key(&self, kid: &str) -> Option<(Algorithm, &DecodingKey)> {
+        self.keys.get(kid).map(|key| (key.algorithm, &key.decoding_key))
+    }
+
+    /// The public half of every configured key, for `/.well-known/jwks.json`.
+    pub fn jwks(&self) -> JwksResponse {
+        JwksResponse {
+            keys: self.keys.values().map(|key| key.jwk.clone()).collect(),
+        }
+    }
+}
+
+fn build_signing_key(cfg: &SigningKeyConfig) -> Result<SigningKey> {
+    match cfg.algorithm.as_str() {
+        "RS256" => build_rsa_key(cfg),
+        "ES256" => build_ec_key(cfg),
+        other => Err(AppError::Internal(format!(
+            "unsupported JWT signing algorithm {other} for kid {}",
+            cfg.kid
+        ))),
+    }
+}
+
+fn build_rsa_key(cfg: &SigningKeyConfig) -> Result<SigningKey> {
+    let private_key = rsa::RsaPrivateKey::from_pkcs8_pem(&cfg.private_key_pem)
+        .or_else(|_| rsa::RsaPrivateKey::from_pkcs1_pem(&cfg.private_key_pem))
+        .map_err(|e| AppError::Internal(format!("failed to parse RSA key {}: {e}", cfg.kid)))?;
+
+    let encoding_key = EncodingKey::from_rsa_pem(cfg.private_key_pem.as_bytes())
+        .map_err(|e| AppError::Internal(format!("failed to load RSA encoding key {}: {e}", cfg.kid)))?;
+
+    let public_key = private_key.to_public_key();
+    let n = URL_SAFE_NO_PAD.encode(public_key.n().to_bytes_be());
+    let e = URL_SAFE_NO_PAD.encode(public_key.e().to_bytes_be());
+    let decoding_key = DecodingKey::from_rsa_components(&n, &e)
+        .map_err(|e| AppError::Internal(format!("failed to build RSA decoding key {}: {e}", cfg.kid)))?;
+
+    Ok(SigningKey {
+        algorithm: Algorithm::RS256,
+        encoding_key,
+        decoding_key,
+        jwk: Jwk {
+            kty: "RSA".to_string(),
+            use_: "sig".to_string(),
+            kid: cfg.kid.clone(),
+            alg: "RS256".to_string(),
+            n: Some(n),
+            e: Some(e),
+            crv: None,
+            x: None,
+            y: None,
+        },
+    })
+}
+
+fn build_ec_key(cfg: &SigningKeyConfig) -> Result<SigningKey> {
+    let secret_key = p256::SecretKey::from_sec1_pem(&cfg.private_key_pem)
+        .or_else(|_| p256::SecretKey::from_pkcs8_pem(&cfg.private_key_pem))
+        .map_err(|e| AppError::Internal(format!("failed to parse EC key {}: {e}", cfg.kid)))?;
+
+    let encoding_key = EncodingKey::from_ec_pem(cfg.private_key_pem.as_bytes())
+        .map_err(|e| AppError::Internal(format!("failed to load EC encoding key {}: {e}", cfg.kid)))?;
+
+    let point = secret_key.public_key().to_encoded_point(false);
+    let x = URL_SAFE_NO_PAD.encode(point.x().ok_or_else(|| {
+        AppError::Internal(format!("EC public key {} is missing an x coordinate", cfg.kid))
+    })?);
+    let y = URL_SAFE_NO_PAD.encode(point.y().ok_or_else(|| {
+        AppError::Internal(format!("EC public key {} is missing a y coordinate", cfg.kid))
+    })?);
+    let decoding_key = DecodingKey::from_ec_components(&x, &y)
+        .map_err(|e| AppError::Internal(format!("failed to build EC decoding key {}: {e}", cfg.kid)))?;
+
+    Ok(SigningKey {
+        algorithm: Algorithm::ES256,
+        encoding_key,
+        decoding_key,
+        jwk: Jwk {
+            kty: "EC".to_string(),
+            use_: "sig".to_string(),
+            kid: cfg.kid.clone(),
+            alg: "ES256".to_string(),
+            n: None,
+            e: None,
+            crv: Some("P-256".to_string()),
+            x: Some(x),
+            y: Some(y),
+        },
+    })
+}
@@ -0,0 +1,324 @@
+//! In-memory account lockout tracking with a sliding attempt window.
+//!
+//! Two trackers run side by side: a per-email tracker (the original flat
+//! lockout) and a per-source-IP tracker. The IP tracker imposes a
+//! progressively longer delay as failures pile up, and escalates to a hard
+//! lock if the failures are spread across enough distinct emails to look
+//! like credential stuffing rather than someone fumbling their own
+//! password. `check_lockout`/`record_attempt` fold both into a single
+//! `retry_after_seconds` so the caller can return one `Retry-After` value.
+
+use std::collections::{HashMap, HashSet};
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone)]
+pub struct LockoutConfig {
+    pub max_attempts: u32,
+    pub lock_duration_ms: u64,
+    pub attempt_window_ms: u64,
+}
+
+#[derive(Debug, Clone)]
+pub struct IpLockoutConfig {
+    /// Failures within the window before progressive delay kicks in.
+    pub soft_threshold: u32,
+    /// Delay unit: a failure past `soft_threshold` waits
+    /// `base_delay_ms * 2^(failures - soft_threshold)`, so the first one
+    /// waits twice this and each further failure doubles it.
+    pub base_delay_ms: u64,
+    /// Upper bound on the progressive delay.
+    pub max_delay_ms: u64,
+    /// Distinct emails attempted from one IP within the window before it's
+    /// treated as credential stuffing and hard-locked.
+    pub distinct_email_threshold: u32,
+    pub lock_duration_ms: u64,
+    pub attempt_window_ms: u64,
+}
+
+#[derive(Debug, Clone, Default)]
+struct AccountState {
+    failures: u32,
+    window_started_at: u64,
+    locked_until: Option<u64>,
+}
+
+#[derive(Debug, Clone, Default)]
+struct IpState {
+    failures: u32,
+    window_started_at: u64,
+    emails_seen: HashSet<String>,
+    /// End of the progressive backoff delay from the most recent failure.
+    delayed_until: Option<u64>,
+    /// End of the hard credential-stuffing lock, if one has been imposed.
+    locked_until: Option<u64>,
+}
+
+pub struct LockoutStatus {
+    pub is_locked: bool,
+    pub locked_until: Option<u64>,
+    pub attempts: u32,
+    pub retry_after_seconds: Option<u64>,
+}
+
+pub struct RecordAttemptStatus {
+    pub is_locked: bool,
+    pub remaining_attempts: u32,
+    pub locked_until: Option<u64>,
+    pub retry_after_seconds: Option<u64>,
+}
+
+pub struct LockoutTracker {
+    config: LockoutConfig,
+    ip_config: IpLockoutConfig,
+    accounts: Mutex<HashMap<String, AccountState>>,
+    ips: Mutex<HashMap<String, IpState>>,
+}
+
+fn now_ms() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_millis() as u64
+}
+
+fn seconds_remaining(until: u64, now: u64) -> u64 {
+    if until > now {
+        (until - now) / 1000
+    } else {
+        0
+    }
+}
+
+impl LockoutTracker {
+    pub fn new(config: LockoutConfig, ip_config: IpLockoutConfig) -> Self {
+        Self {
+            config,
+            ip_config,
+            accounts: Mutex::new(HashMap::new()),
+            ips: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn check_lockout(&self, email: &str, ip: Option<&str>) -> LockoutStatus {
+        let now = now_ms();
+
+        let account_state = {
+            let accounts = self.accounts.lock().expect("lockout tracker lock poisoned");
+            accounts.get(email).cloned().unwrap_or_default()
+        };
+        let is_locked = account_state.locked_until.map(|until| until > now).unwrap_or(false);
+        let email_retry = account_state.locked_until.map(|until| seconds_remaining(until, now));
+
+        let ip_retry = ip.and_then(|ip| self.ip_retry_after(ip, now));
+        let ip_locked = ip.map(|ip| self.ip_is_hard_locked(ip, now)).unwrap_or(false);
+
+        LockoutStatus {
+            is_locked: is_locked || ip_locked,
+            locked_until: account_state.locked_until,
+            attempts: account_state.failures,
+            retry_after_seconds: max_option(email_retry, ip_retry),
+        }
+    }
+
+    pub fn record_attempt(&self, email: &str, ip: Option<&str>, success: bool) -> RecordAttemptStatus {
+        let now = now_ms();
+
+        let (is_locked, remaining_attempts, locked_until, email_retry) =
+            self.record_email_attempt(email, success, now);
+        let ip_retry = ip.map(|ip| self.record_ip_attempt(ip, email, success, now));
+
+        let ip_locked = ip_retry.as_ref().map(|(hard_locked, _)| *hard_locked).unwrap_or(false);
+        let ip_retry_after = ip_retry.and_then(|(_, retry)| retry);
+
+        RecordAttemptStatus {
+            is_locked: is_locked || ip_locked,
+            remaining_attempts,
+            locked_until,
+            retry_after_seconds: max_option(email_retry, ip_retry_after),
+        }
+    }
+
+    /// Returns `(is_locked, remaining_attempts, locked_until, retry_after_seconds)`.
+    fn record_email_attempt(&self, email: &str, success: bool, now: u64) -> (bool, u32, Option<u64>, Option<u64>) {
+        let mut accounts = self.accounts.lock().expect("lockout tracker lock poisoned");
+        let state = accounts.entry(email.to_string()).or_default();
+
+        // A still-active lock takes priority over recording a new attempt.
+        if let Some(until) = state.locked_until {
+            if until > now {
+                return (true, 0, Some(until), Some(seconds_remaining(until, now)));
+            }
+            // Lock has expired; reset the window.
+            *state = AccountState::default();
+        }
+
+        if success {
+            *state = AccountState::default();
+            return (false, self.config.max_attempts, None, None);
+        }
+
+        if state.window_started_at == 0 || now - state.window_started_at > self.config.attempt_window_ms {
+            state.window_started_at = now;
+            state.failures = 0;
+        }
+
+        state.failures += 1;
+
+        if state.failures >= self.config.max_attempts {
+            let locked_until = now + self.config.lock_duration_ms;
+            state.locked_until = Some(locked_until);
+            return (true, 0, Some(locked_until), Some(seconds_remaining(locked_until, now)));
+        }
+
+        (false, self.config.max_attempts - state.failures, None, None)
+    }
+
+    /// Returns `(is_hard_locked, retry_after_seconds)`.
+    fn record_ip_attempt(&self, ip: &str, email: &str, success: bool, now: u64) -> (bool, Option<u64>) {
+        let mut ips = self.ips.lock().expect("lockout tracker lock poisoned");
+        let state = ips.entry(ip.to_string()).or_default();
+
+        if let Some(until) = state.locked_until {
+            if until > now {
+                return (true, Some(seconds_remaining(until, now)));
+            }
+            *state = IpState::default();
+        }
+
+        if success {
+            // Only clear the progressive delay, not the whole state — a
+            // credential-stuffing attacker who gets one hit (e.g. against an
+            // account they also control) shouldn't get to wipe out the
+            // distinct-email history that the stuffing check relies on.
+            state.failures = 0;
+            state.delayed_until = None;
+            return (false, None);
+        }
+
+        if state.window_started_at == 0 || now - state.window_started_at > self.ip_config.attempt_window_ms {
+            *state = IpState {
+                window_started_at: now,
+                ..IpState::default()
+            };
+        }
+
+        state.failures += 1;
+        state.emails_seen.insert(email.to_string());
+
+        // Failures spread across many distinct emails from one IP look like
+        // credential stuffing rather than one user mistyping a password;
+        // that gets a hard lock instead of a delay.
+        if state.emails_seen.len() as u32 >= self.ip_config.distinct_email_threshold {
+            let locked_until = now + self.ip_config.lock_duration_ms;
+            state.locked_until = Some(locked_until);
+            return (true, Some(seconds_remaining(locked_until, now)));
+        }
+
+        if state.failures > self.ip_config.soft_threshold {
+            let exponent = state.failures - self.ip_config.soft_threshold;
+            let delay_ms = self
+                .ip_config
+                .base_delay_ms
+                .saturating_mul(1u64 << exponent.min(32))
+                .min(self.ip_config.max_delay_ms);
+            let delayed_until = now + delay_ms;
+            state.delayed_until = Some(delayed_until);
+            return (false, Some(seconds_remaining(delayed_until, now)));
+        }
+
+        (false, None)
+    }
+
+    fn ip_retry_after(&self, ip: &str, now: u64) -> Option<u64> {
+        let ips = self.ips.lock().expect("lockout tracker lock poisoned");
+        let state = ips.get(ip)?;
+        let locked = state.locked_until.map(|until| seconds_remaining(until, now));
+        let delayed = state.delayed_until.map(|until| seconds_remaining(until, now));
+        max_option(locked, delayed)
+    }
+
+    fn ip_is_hard_locked(&self, ip: &str, now: u64) -> bool {
+        let ips = self.ips.lock().expect("lockout tracker lock poisoned");
+        ips.get(ip).and_then(|state| state.locked_until).map(|until| until > now).unwrap_or(false)
+    }
+}
+
+fn max_option(a: Option<u64>, b: Option<u64>) -> Option<u64> {
+    match (a, b) {
+        (Some(a), Some(b)) => Some(a.max(b)),
+        (Some(a), None) => Some(a),
+        (None, Some(b)) => Some(b),
+        (None, None) => None,
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CheckLockoutRequest {
+    pub email: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CheckLockoutResponse {
+    pub is_locked: bool,
+    pub locked_until: Option<u64>,
+    pub attempts: u32,
+    pub time_remaining_seconds: Option<u64>,
+    pub retry_after_seconds: Option<u64>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RecordAttemptRequest {
+    pub email: String,
+    pub success: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RecordAttemptResponse {
+    pub is_locked: bool,
+    pub remaining_attempts: Option<u32>,
+    pub locked_until: Option<u64>,
+    pub retry_after_seconds: Option<u64>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tracker() -> LockoutTracker {
+        LockoutTracker::new(
+            LockoutConfig {
+                max_attempts: 1000,
+                lock_duration_ms: 60_000,
+                attempt_window_ms: 60_000,
+            },
+            IpLockoutConfig {
+                soft_threshold: 1000,
+                base_delay_ms: 1000,
+                max_delay_ms: 60_000,
+                distinct_email_threshold: 3,
+                lock_duration_ms: 60_000,
+                attempt_window_ms: 60_000,
+            },
+        )
+    }
+
+    #[test]
+    fn successful_login_does_not_reset_distinct_email_history_for_ip() {
+        let tracker = tracker();
+        let ip = "198.51.100.7";
+
+        // Credential stuffing across two distinct emails from one IP...
+        tracker.record_attempt("victim-a@example.com", Some(ip), false);
+        tracker.record_attempt("victim-b@example.com", Some(ip), false);
+
+        // ...then one successful login (e.g. against an account the
+        // attacker also controls) should not wipe the IP's history.
+        let status = tracker.record_attempt("attacker@example.com", Some(ip), true);
+        assert!(!status.is_locked);
+
+        // A third distinct email should still trip the stuffing threshold,
+        // because `emails_seen` survived the success above.
+        let status = tracker.record_attempt("victim-c@example.com", Some(ip), false);
+        assert!(status.is_locked);
+    }
+}
@@ -10,7 +10,7 @@
 use actix_web::{
     dev::{Service, ServiceRequest},
     http::header::{HeaderName, HeaderValue},
-    middleware, web, App, HttpMessage, HttpResponse, HttpServer,
+    middleware, web, App, HttpMessage, HttpRequest, HttpResponse, HttpServer,
 };
 use opentelemetry::{
     global,
@@ -30,17 +30,20 @@ use uuid::Uuid;
 
 mod config;
 mod db;
+mod email_otp;
 mod encryption;
 mod error;
 mod jwt;
+mod keys;
 mod lockout;
 mod password;
 mod totp;
+mod webauthn;
 
 use config::Config;
 use encryption::EncryptionConfig;
 use error::Result;
-use lockout::{LockoutConfig, LockoutTracker};
+use lockout::{IpLockoutConfig, LockoutConfig, LockoutTracker};
 
 #[derive(Clone)]
 struct RequestContext {
@@ -65,6 +68,20 @@ fn header_value(req: &ServiceRequest, name: &str) -> Option<String> {
     req.headers().get(name).and_then(|v| v.to_str().ok()).map(|v| v.to_string())
 }
 
+/// Best-effort source IP for the lockout tracker's per-IP throttling: the
+/// first `X-Forwarded-For` hop if the gateway set one, else the peer
+/// address. Callers treat this as advisory, not as an authenticated
+/// identity.
+fn client_ip(req: &HttpRequest) -> Option<String> {
+    req.headers()
+        .get("x-forwarded-for")
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.split(',').next())
+        .map(|v| v.trim().to_string())
+        .filter(|v| !v.is_empty())
+        .or_else(|| req.peer_addr().map(|addr| addr.ip().to_string()))
+}
+
 fn build_request_context(req: &ServiceRequest) -> RequestContext {
     let request_id = header_value(req, "x-request-id")
         .filter(|value| !value.trim().is_empty())
@@ -147,6 +164,12 @@ struct AppState {
     config: Config,
     encryption_config: EncryptionConfig,
     lockout_tracker: LockoutTracker,
+    refresh_tracker: jwt::RefreshTokenTracker,
+    /// `None` means JWTs are signed with `config.jwt_secret` (HS256); when
+    /// present, access tokens are signed with its active RS256/ES256 key.
+    key_manager: Option<keys::KeyManager>,
+    webauthn_challenges: webauthn::ChallengeStore,
+    email_otps: email_otp::EmailOtpStore,
     // db_pool: sqlx::PgPool, // Uncomment when database is connected
 }
 
@@ -170,15 +193,27 @@ async fn ready() -> HttpResponse {
 // Password Handlers
 // ============================================================================
 
-/// Hash a password.
+/// Hash a password. Always runs the HIBP breach check first; if the caller
+/// set `reject_breach_count_at_or_above` and the count meets it, the
+/// password is rejected instead of hashed.
 async fn hash_password(
     state: web::Data<Arc<AppState>>,
     body: web::Json<password::HashPasswordRequest>,
 ) -> Result<HttpResponse> {
+    let breach_count = password::check_breach_count(&body.password, &state.config).await?;
+
+    if let Some(threshold) = body.reject_breach_count_at_or_above {
+        if breach_count >= threshold {
+            return Err(error::AppError::Validation(format!(
+                "password has appeared in {breach_count} known breaches"
+            )));
+        }
+    }
+
     let cost = body.cost.unwrap_or(state.config.bcrypt_cost);
     let hash = password::hash_password(&body.password, Some(cost))?;
 
-    Ok(HttpResponse::Ok().json(password::HashPasswordResponse { hash }))
+    Ok(HttpResponse::Ok().json(password::HashPasswordResponse { hash, breach_count }))
 }
 
 /// Verify a password.
@@ -190,39 +225,93 @@ async fn verify_password(
     Ok(HttpResponse::Ok().json(password::VerifyPasswordResponse { valid }))
 }
 
+/// Check whether a password has appeared in a known breach, without
+/// hashing or persisting it.
+async fn check_breach(
+    state: web::Data<Arc<AppState>>,
+    body: web::Json<password::CheckBreachRequest>,
+) -> Result<HttpResponse> {
+    let breach_count = password::check_breach_count(&body.password, &state.config).await?;
+
+    Ok(HttpResponse::Ok().json(password::CheckBreachResponse { breach_count }))
+}
+
 // ============================================================================
 // JWT Handlers
 // ============================================================================
 
-/// Create a JWT token.
+/// Create a JWT token. If `issue_refresh` is set, also mints a paired
+/// refresh token that starts a new token family.
 async fn create_jwt(
     state: web::Data<Arc<AppState>>,
     body: web::Json<jwt::CreateJwtRequest>,
 ) -> Result<HttpResponse> {
     let ttl = body.ttl_seconds.unwrap_or(state.config.jwt_ttl_seconds);
 
+    if body.issue_refresh.unwrap_or(false) {
+        let refresh_ttl = body
+            .refresh_ttl_seconds
+            .unwrap_or(state.config.refresh_ttl_seconds);
+        let pair = jwt::issue_token_pair(
+            &body.user_id,
+            &body.email,
+            &state.config.jwt_secret,
+            ttl,
+            refresh_ttl,
+            &state.refresh_tracker,
+            state.key_manager.as_ref(),
+        )?;
+        return Ok(HttpResponse::Ok().json(jwt::CreateJwtResponse {
+            token: pair.access_token,
+            expires_at: pair.access_expires_at,
+            refresh_token: Some(pair.refresh_token),
+            refresh_expires_at: Some(pair.refresh_expires_at),
+        }));
+    }
+
     let (token, expires_at) = jwt::create_jwt(
         &body.user_id,
         &body.email,
         &state.config.jwt_secret,
         ttl,
         body.token_type.as_deref(),
+        state.key_manager.as_ref(),
     )?;
 
-    Ok(HttpResponse::Ok().json(jwt::CreateJwtResponse { token, expires_at }))
+    Ok(HttpResponse::Ok().json(jwt::CreateJwtResponse {
+        token,
+        expires_at,
+        refresh_token: None,
+        refresh_expires_at: None,
+    }))
 }
 
-/// Validate a JWT token.
+/// Validate a JWT token. Rejects tokens whose `jti` has been revoked,
+/// individually or as part of a family revoked by reuse detection.
 async fn validate_jwt(
     state: web::Data<Arc<AppState>>,
     body: web::Json<jwt::ValidateJwtRequest>,
 ) -> Result<HttpResponse> {
-    match jwt::validate_jwt(&body.token, &state.config.jwt_secret) {
-        Ok(claims) => Ok(HttpResponse::Ok().json(jwt::ValidateJwtResponse {
-            valid: true,
-            claims: Some(claims),
-            error: None,
-        })),
+    match jwt::validate_jwt(&body.token, &state.config.jwt_secret, state.key_manager.as_ref()) {
+        Ok(claims) => {
+            if claims
+                .jti
+                .as_deref()
+                .map(|jti| state.refresh_tracker.is_revoked(jti))
+                .unwrap_or(false)
+            {
+                return Ok(HttpResponse::Ok().json(jwt::ValidateJwtResponse {
+                    valid: false,
+                    claims: None,
+                    error: Some("token has been revoked".to_string()),
+                }));
+            }
+            Ok(HttpResponse::Ok().json(jwt::ValidateJwtResponse {
+                valid: true,
+                claims: Some(claims),
+                error: None,
+            }))
+        }
         Err(e) => Ok(HttpResponse::Ok().json(jwt::ValidateJwtResponse {
             valid: false,
             claims: None,
@@ -231,6 +320,50 @@ async fn validate_jwt(
     }
 }
 
+/// Validate an incoming refresh token and rotate it, issuing a new
+/// access+refresh pair in the same token family.
+async fn refresh_jwt(
+    state: web::Data<Arc<AppState>>,
+    body: web::Json<jwt::RefreshJwtRequest>,
+) -> Result<HttpResponse> {
+    let pair = jwt::rotate_refresh_token(
+        &body.refresh_token,
+        &state.config.jwt_secret,
+        state.config.jwt_ttl_seconds,
+        state.config.refresh_ttl_seconds,
+        &state.refresh_tracker,
+        state.key_manager.as_ref(),
+    )?;
+
+    Ok(HttpResponse::Ok().json(jwt::RefreshJwtResponse {
+        token: pair.access_token,
+        expires_at: pair.access_expires_at,
+        refresh_token: pair.refresh_token,
+        refresh_expires_at: pair.refresh_expires_at,
+    }))
+}
+
+/// Blacklist a `jti` so `validate_jwt` rejects it going forward.
+async fn revoke_jwt(
+    state: web::Data<Arc<AppState>>,
+    body: web::Json<jwt::RevokeJwtRequest>,
+) -> Result<HttpResponse> {
+    state.refresh_tracker.revoke_jti(&body.jti);
+
+    Ok(HttpResponse::Ok().json(jwt::RevokeJwtResponse { revoked: true }))
+}
+
+/// Publish the public half of every active signing key as a JWKS, so
+/// downstream services can verify RS256/ES256 access tokens offline. Empty
+/// when the service is running in HMAC-only mode.
+async fn jwks(state: web::Data<Arc<AppState>>) -> HttpResponse {
+    let jwks = match &state.key_manager {
+        Some(key_manager) => key_manager.jwks(),
+        None => keys::JwksResponse { keys: Vec::new() },
+    };
+    HttpResponse::Ok().json(jwks)
+}
+
 // ============================================================================
 // TOTP Handlers
 // ============================================================================
@@ -275,6 +408,83 @@ async fn verify_backup_code(
     }))
 }
 
+// ============================================================================
+// WebAuthn Handlers
+// ============================================================================
+
+/// Start a WebAuthn registration ceremony; returns options for `navigator.credentials.create()`.
+async fn webauthn_register_start(
+    state: web::Data<Arc<AppState>>,
+    body: web::Json<webauthn::RegisterStartRequest>,
+) -> Result<HttpResponse> {
+    let (session_id, options) =
+        webauthn::start_registration(&state.webauthn_challenges, &state.config, &body.user_id, &body.email);
+
+    Ok(HttpResponse::Ok().json(webauthn::RegisterStartResponse { session_id, options }))
+}
+
+/// Finish a WebAuthn registration ceremony, verifying the attestation.
+async fn webauthn_register_finish(
+    state: web::Data<Arc<AppState>>,
+    body: web::Json<webauthn::RegisterFinishRequest>,
+) -> Result<HttpResponse> {
+    let response = webauthn::finish_registration(&state.webauthn_challenges, &state.config, &body)?;
+
+    Ok(HttpResponse::Ok().json(response))
+}
+
+/// Start a WebAuthn authentication ceremony; returns options for `navigator.credentials.get()`.
+async fn webauthn_authenticate_start(
+    state: web::Data<Arc<AppState>>,
+    body: web::Json<webauthn::AuthenticateStartRequest>,
+) -> Result<HttpResponse> {
+    let (session_id, options) =
+        webauthn::start_authentication(&state.webauthn_challenges, &state.config, &body.credential_ids);
+
+    Ok(HttpResponse::Ok().json(webauthn::AuthenticateStartResponse { session_id, options }))
+}
+
+/// Finish a WebAuthn authentication ceremony, verifying the assertion signature.
+async fn webauthn_authenticate_finish(
+    state: web::Data<Arc<AppState>>,
+    body: web::Json<webauthn::AuthenticateFinishRequest>,
+) -> Result<HttpResponse> {
+    let response = webauthn::finish_authentication(&state.webauthn_challenges, &state.config, &body)?;
+
+    Ok(HttpResponse::Ok().json(response))
+}
+
+// ============================================================================
+// Email OTP Handlers
+// ============================================================================
+
+/// Generate an email one-time-code for accounts without a TOTP
+/// authenticator enrolled. The plaintext code is returned to the caller
+/// for delivery; this service never sends mail itself.
+async fn generate_email_otp(
+    state: web::Data<Arc<AppState>>,
+    body: web::Json<email_otp::GenerateEmailOtpRequest>,
+) -> Result<HttpResponse> {
+    let config = email_otp::EmailOtpConfig {
+        ttl_seconds: state.config.email_otp_ttl_seconds,
+        max_attempts: state.config.email_otp_max_attempts,
+        bcrypt_cost: state.config.bcrypt_cost,
+    };
+    let response = email_otp::generate(&state.email_otps, &config, &body.email)?;
+
+    Ok(HttpResponse::Ok().json(response))
+}
+
+/// Verify an email one-time-code.
+async fn verify_email_otp(
+    state: web::Data<Arc<AppState>>,
+    body: web::Json<email_otp::VerifyEmailOtpRequest>,
+) -> Result<HttpResponse> {
+    let response = email_otp::verify(&state.email_otps, &body.email, &body.code)?;
+
+    Ok(HttpResponse::Ok().json(response))
+}
+
 // ============================================================================
 // Encryption Handlers
 // ============================================================================
@@ -308,12 +518,14 @@ async fn decrypt_data(
 // Lockout Handlers
 // ============================================================================
 
-/// Check if an account is locked.
+/// Check if an account (or its source IP) is locked.
 async fn check_lockout(
+    http_req: HttpRequest,
     state: web::Data<Arc<AppState>>,
     body: web::Json<lockout::CheckLockoutRequest>,
 ) -> Result<HttpResponse> {
-    let status = state.lockout_tracker.check_lockout(&body.email);
+    let ip = client_ip(&http_req);
+    let status = state.lockout_tracker.check_lockout(&body.email, ip.as_deref());
 
     let time_remaining = status.locked_until.map(|lu| {
         let now = std::time::SystemTime::now()
@@ -327,20 +539,26 @@ async fn check_lockout(
         }
     });
 
-    Ok(HttpResponse::Ok().json(lockout::CheckLockoutResponse {
-        is_locked: status.is_locked,
-        locked_until: status.locked_until,
-        attempts: status.attempts,
-        time_remaining_seconds: time_remaining,
-    }))
+    Ok(retry_after_response(
+        status.retry_after_seconds,
+        lockout::CheckLockoutResponse {
+            is_locked: status.is_locked,
+            locked_until: status.locked_until,
+            attempts: status.attempts,
+            time_remaining_seconds: time_remaining,
+            retry_after_seconds: status.retry_after_seconds,
+        },
+    ))
 }
 
 /// Record a login attempt.
 async fn record_attempt(
+    http_req: HttpRequest,
     state: web::Data<Arc<AppState>>,
     body: web::Json<lockout::RecordAttemptRequest>,
 ) -> Result<HttpResponse> {
-    let status = state.lockout_tracker.record_attempt(&body.email, body.success);
+    let ip = client_ip(&http_req);
+    let status = state.lockout_tracker.record_attempt(&body.email, ip.as_deref(), body.success);
 
     let remaining = if status.is_locked {
         None
@@ -348,11 +566,26 @@ async fn record_attempt(
         Some(status.remaining_attempts)
     };
 
-    Ok(HttpResponse::Ok().json(lockout::RecordAttemptResponse {
-        is_locked: status.is_locked,
-        remaining_attempts: remaining,
-        locked_until: status.locked_until,
-    }))
+    Ok(retry_after_response(
+        status.retry_after_seconds,
+        lockout::RecordAttemptResponse {
+            is_locked: status.is_locked,
+            remaining_attempts: remaining,
+            locked_until: status.locked_until,
+            retry_after_seconds: status.retry_after_seconds,
+        },
+    ))
+}
+
+/// `429 Too Many Requests` with a `Retry-After` header when the tracker
+/// reports a nonzero delay, otherwise a plain `200` with the same body.
+fn retry_after_response<T: serde::Serialize>(retry_after_seconds: Option<u64>, body: T) -> HttpResponse {
+    match retry_after_seconds {
+        Some(seconds) if seconds > 0 => HttpResponse::TooManyRequests()
+            .insert_header(("Retry-After", seconds.to_string()))
+            .json(body),
+        _ => HttpResponse::Ok().json(body),
+    }
 }
 
 // ============================================================================
@@ -381,13 +614,38 @@ async fn main() -> std::io::Result<()> {
         lock_duration_ms: config.lockout_duration_ms,
         attempt_window_ms: config.lockout_window_ms,
     };
-    let lockout_tracker = LockoutTracker::new(lockout_config);
+    let ip_lockout_config = IpLockoutConfig {
+        soft_threshold: config.ip_lockout_soft_threshold,
+        base_delay_ms: config.ip_lockout_base_delay_ms,
+        max_delay_ms: config.ip_lockout_max_delay_ms,
+        distinct_email_threshold: config.ip_lockout_distinct_email_threshold,
+        lock_duration_ms: config.ip_lockout_duration_ms,
+        attempt_window_ms: config.ip_lockout_window_ms,
+    };
+    let lockout_tracker = LockoutTracker::new(lockout_config, ip_lockout_config);
+
+    // Load asymmetric JWT signing keys, if configured; otherwise the
+    // service stays in HMAC-only mode.
+    let key_manager = if config.jwt_signing_keys.trim().is_empty() {
+        None
+    } else {
+        let key_configs: Vec<keys::SigningKeyConfig> = serde_json::from_str(&config.jwt_signing_keys)
+            .expect("JWT_SIGNING_KEYS must be a JSON array of signing key configs");
+        Some(
+            keys::KeyManager::from_configs(&key_configs, &config.jwt_active_kid)
+                .expect("failed to initialize JWT key manager"),
+        )
+    };
 
     // Create app state
     let state = Arc::new(AppState {
         config: config.clone(),
         encryption_config,
         lockout_tracker,
+        refresh_tracker: jwt::RefreshTokenTracker::new(),
+        key_manager,
+        webauthn_challenges: webauthn::ChallengeStore::new(),
+        email_otps: email_otp::EmailOtpStore::new(),
     });
 
     let bind_addr = format!("{}:{}", config.host, config.port);
@@ -440,13 +698,25 @@ async fn main() -> std::io::Result<()> {
             // Password endpoints
             .route("/api/auth/hash-password", web::post().to(hash_password))
             .route("/api/auth/verify-password", web::post().to(verify_password))
+            .route("/api/auth/password/check-breach", web::post().to(check_breach))
             // JWT endpoints
             .route("/api/auth/create-jwt", web::post().to(create_jwt))
             .route("/api/auth/validate-jwt", web::post().to(validate_jwt))
+            .route("/api/auth/refresh-jwt", web::post().to(refresh_jwt))
+            .route("/api/auth/revoke-jwt", web::post().to(revoke_jwt))
+            .route("/.well-known/jwks.json", web::get().to(jwks))
             // TOTP endpoints
             .route("/api/auth/totp/generate", web::post().to(generate_totp))
             .route("/api/auth/totp/verify", web::post().to(verify_totp))
             .route("/api/auth/totp/verify-backup", web::post().to(verify_backup_code))
+            // WebAuthn endpoints
+            .route("/api/auth/webauthn/register/start", web::post().to(webauthn_register_start))
+            .route("/api/auth/webauthn/register/finish", web::post().to(webauthn_register_finish))
+            .route("/api/auth/webauthn/authenticate/start", web::post().to(webauthn_authenticate_start))
+            .route("/api/auth/webauthn/authenticate/finish", web::post().to(webauthn_authenticate_finish))
+            // Email OTP endpoints
+            .route("/api/auth/email-otp/generate", web::post().to(generate_email_otp))
+            .route("/api/auth/email-otp/verify", web::post().to(verify_email_otp))
             // Encryption endpoints
             .route("/api/auth/encrypt", web::post().to(encrypt_data))
             .route("/api/auth/decrypt", web::post().to(decrypt_data))
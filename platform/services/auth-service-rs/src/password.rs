@@ -0,0 +1,155 @@
+//! Password hashing (bcrypt) plus a HaveIBeenPwned k-anonymity breach check.
+
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+use sha1::{Digest, Sha1};
+
+use crate::config::Config;
+use crate::error::{AppError, Result};
+
+#[derive(Debug, Deserialize)]
+pub struct HashPasswordRequest {
+    pub password: String,
+    pub cost: Option<u32>,
+    /// If set, reject (rather than merely flag) passwords seen this many
+    /// times or more in the HIBP corpus.
+    pub reject_breach_count_at_or_above: Option<u64>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct HashPasswordResponse {
+    pub hash: String,
+    /// Number of times the password has appeared in known breaches (the
+    /// check always runs). `0` means no match was found or, with
+    /// `hibp_fail_open`, that the lookup failed.
+    pub breach_count: u64,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct VerifyPasswordRequest {
+    pub password: String,
+    pub hash: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct VerifyPasswordResponse {
+    pub valid: bool,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CheckBreachRequest {
+    pub password: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CheckBreachResponse {
+    pub breach_count: u64,
+}
+
+pub fn hash_password(password: &str, cost: Option<u32>) -> Result<String> {
+    let cost = cost.unwrap_or(bcrypt::DEFAULT_COST);
+    bcrypt::hash(password, cost).map_err(|e| AppError::Crypto(format!("bcrypt hash failed: {e}")))
+}
+
+pub fn verify_password(password: &str, hash: &str) -> Result<bool> {
+    bcrypt::verify(password, hash).map_err(|e| AppError::Crypto(format!("bcrypt verify failed: {e}")))
+}
+
+/// Split the uppercase hex SHA-1 of `password` into the 5-character prefix
+/// sent to the HIBP range API and the 35-character suffix matched locally,
+/// so the plaintext (and even the full hash) never leaves the service.
+fn sha1_prefix_suffix(password: &str) -> (String, String) {
+    let mut hasher = Sha1::new();
+    hasher.update(password.as_bytes());
+    let digest = hasher.finalize();
+    let hex = format!("{digest:x}").to_uppercase();
+    (hex[..5].to_string(), hex[5..].to_string())
+}
+
+/// A brief negative-result cache so repeated checks against an unbreached
+/// prefix (the common case) don't pay the round-trip every time.
+struct NegativeCache {
+    entries: Mutex<std::collections::HashMap<String, Instant>>,
+    ttl: Duration,
+}
+
+impl NegativeCache {
+    fn new(ttl: Duration) -> Self {
+        Self {
+            entries: Mutex::new(std::collections::HashMap::new()),
+            ttl,
+        }
+    }
+
+    fn is_fresh_negative(&self, prefix: &str) -> bool {
+        let entries = self.entries.lock().expect("negative cache lock poisoned");
+        matches!(entries.get(prefix), Some(seen_at) if seen_at.elapsed() < self.ttl)
+    }
+
+    fn mark_negative(&self, prefix: &str) {
+        let mut entries = self.entries.lock().expect("negative cache lock poisoned");
+        entries.insert(prefix.to_string(), Instant::now());
+    }
+}
+
+static NEGATIVE_CACHE: once_cell::sync::Lazy<NegativeCache> =
+    once_cell::sync::Lazy::new(|| NegativeCache::new(Duration::from_secs(60)));
+
+/// Query the HaveIBeenPwned range API using k-anonymity and return how many
+/// times the password has been seen in a breach (0 if never).
+///
+/// Honors `config.hibp_fail_open`: a timeout, network error, or non-200
+/// response is treated as "not breached" when fail-open, or surfaced as
+/// `AppError::Internal` when fail-closed.
+pub async fn check_breach_count(password: &str, config: &Config) -> Result<u64> {
+    let (prefix, suffix) = sha1_prefix_suffix(password);
+
+    if NEGATIVE_CACHE.is_fresh_negative(&prefix) {
+        return Ok(0);
+    }
+
+    let client = match reqwest::Client::builder()
+        .timeout(Duration::from_millis(config.hibp_timeout_ms))
+        .build()
+    {
+        Ok(client) => client,
+        Err(e) => return handle_hibp_failure(config, &format!("failed to build HTTP client: {e}")),
+    };
+
+    let url = format!("{}/{}", config.hibp_range_endpoint, prefix);
+    let response = match client.get(&url).send().await {
+        Ok(response) => response,
+        Err(e) => return handle_hibp_failure(config, &format!("HIBP request failed: {e}")),
+    };
+
+    if !response.status().is_success() {
+        return handle_hibp_failure(config, &format!("HIBP returned status {}", response.status()));
+    }
+
+    let body = match response.text().await {
+        Ok(body) => body,
+        Err(e) => return handle_hibp_failure(config, &format!("failed to read HIBP response: {e}")),
+    };
+
+    for line in body.lines() {
+        if let Some((candidate_suffix, count)) = line.split_once(':') {
+            if candidate_suffix.eq_ignore_ascii_case(&suffix) {
+                return Ok(count.trim().parse().unwrap_or(0));
+            }
+        }
+    }
+
+    NEGATIVE_CACHE.mark_negative(&prefix);
+    Ok(0)
+}
+
+fn handle_hibp_failure(config: &Config, message: &str) -> Result<u64> {
+    if config.hibp_fail_open {
+        tracing::warn!("HIBP breach check failed open: {message}");
+        Ok(0)
+    } else {
+        Err(AppError::Internal(format!("HIBP breach check failed: {message}")))
+    }
+}
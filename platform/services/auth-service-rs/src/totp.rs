@@ -0,0 +1,110 @@
+//! TOTP secret generation, code verification, and backup codes.
+
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use totp_rs::{Algorithm, TOTP};
+
+use crate::error::{AppError, Result};
+
+pub struct TotpSetup {
+    pub secret: String,
+    pub otpauth_url: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct GenerateTotpRequest {
+    pub email: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct GenerateTotpResponse {
+    pub secret: String,
+    pub otpauth_url: String,
+    pub backup_codes: Vec<String>,
+    pub backup_codes_hashed: Vec<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct VerifyTotpRequest {
+    pub code: String,
+    pub secret: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct VerifyTotpResponse {
+    pub valid: bool,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct VerifyBackupCodeRequest {
+    pub code: String,
+    pub hashed_codes: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct VerifyBackupCodeResponse {
+    pub valid: bool,
+    pub used_index: Option<usize>,
+}
+
+fn build_totp(secret: &[u8], email: &str) -> Result<TOTP> {
+    TOTP::new(
+        Algorithm::SHA1,
+        6,
+        1,
+        30,
+        secret.to_vec(),
+        Some("Campreserv".to_string()),
+        email.to_string(),
+    )
+    .map_err(|e| AppError::Crypto(format!("failed to build TOTP: {e}")))
+}
+
+pub fn generate_totp_secret(email: &str) -> TotpSetup {
+    let secret = totp_rs::Secret::generate_secret();
+    let secret_bytes = secret.to_bytes().expect("generated secret is valid");
+    let totp = build_totp(&secret_bytes, email).expect("generated secret builds a valid TOTP");
+
+    TotpSetup {
+        secret: secret.to_encoded().to_string(),
+        otpauth_url: totp.get_url(),
+    }
+}
+
+pub fn verify_totp(code: &str, secret: &str) -> Result<bool> {
+    let decoded = totp_rs::Secret::Encoded(secret.to_string())
+        .to_bytes()
+        .map_err(|e| AppError::Validation(format!("invalid TOTP secret: {e}")))?;
+    let totp = build_totp(&decoded, "")?;
+
+    totp.check_current(code)
+        .map_err(|e| AppError::Internal(format!("failed to check TOTP code: {e}")))
+}
+
+const BACKUP_CODE_COUNT: usize = 10;
+
+/// Generate a batch of one-time backup codes, returning `(plaintext,
+/// bcrypt_hash)` pairs. Only the hashes should be persisted.
+pub fn generate_backup_codes() -> Result<Vec<(String, String)>> {
+    let mut rng = rand::thread_rng();
+    (0..BACKUP_CODE_COUNT)
+        .map(|_| {
+            let code: String = (0..10).map(|_| rng.gen_range(0..10).to_string()).collect();
+            let hash = bcrypt::hash(&code, bcrypt::DEFAULT_COST)
+                .map_err(|e| AppError::Crypto(format!("failed to hash backup code: {e}")))?;
+            Ok((code, hash))
+        })
+        .collect()
+}
+
+/// Compare `code` against each hashed backup code, returning the index of
+/// the first match (callers are responsible for removing it so it can't be
+/// reused).
+pub fn verify_backup_code(code: &str, hashed_codes: &[String]) -> Result<Option<usize>> {
+    for (index, hashed) in hashed_codes.iter().enumerate() {
+        if bcrypt::verify(code, hashed).map_err(|e| AppError::Crypto(format!("bcrypt verify failed: {e}")))? {
+            return Ok(Some(index));
+        }
+    }
+    Ok(None)
+}
@@ -0,0 +1,81 @@
+//! Site availability checking against existing reservations and maintenance
+//! blocks.
+
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Deserialize)]
+pub struct CheckAvailabilityRequest {
+    pub arrival_date: NaiveDate,
+    pub departure_date: NaiveDate,
+}
+
+#[derive(Debug, Clone)]
+pub struct SiteInfo {
+    pub id: String,
+    pub name: String,
+    pub site_class_id: String,
+    pub base_rate_cents: Option<u32>,
+}
+
+#[derive(Debug, Clone)]
+pub struct ExistingReservation {
+    pub site_id: String,
+    pub arrival_date: NaiveDate,
+    pub departure_date: NaiveDate,
+    pub status: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct MaintenanceBlock {
+    pub site_id: String,
+    pub start_date: NaiveDate,
+    pub end_date: NaiveDate,
+    pub reason: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct AvailableSite {
+    pub id: String,
+    pub name: String,
+    pub site_class_id: String,
+    pub base_rate_cents: Option<u32>,
+}
+
+fn ranges_overlap(a_start: NaiveDate, a_end: NaiveDate, b_start: NaiveDate, b_end: NaiveDate) -> bool {
+    a_start < b_end && b_start < a_end
+}
+
+const ACTIVE_RESERVATION_STATUSES: &[&str] = &["confirmed", "checked_in", "pending"];
+
+/// Filter `sites` down to the ones with no conflicting reservation or
+/// maintenance block over `[arrival_date, departure_date)`.
+pub fn filter_available_sites(
+    sites: &[SiteInfo],
+    arrival_date: NaiveDate,
+    departure_date: NaiveDate,
+    reservations: &[ExistingReservation],
+    maintenance: &[MaintenanceBlock],
+) -> Vec<AvailableSite> {
+    sites
+        .iter()
+        .filter(|site| {
+            let blocked_by_reservation = reservations.iter().any(|r| {
+                r.site_id == site.id
+                    && ACTIVE_RESERVATION_STATUSES.contains(&r.status.as_str())
+                    && ranges_overlap(arrival_date, departure_date, r.arrival_date, r.departure_date)
+            });
+            let blocked_by_maintenance = maintenance.iter().any(|m| {
+                m.site_id == site.id
+                    && ranges_overlap(arrival_date, departure_date, m.start_date, m.end_date)
+            });
+            !blocked_by_reservation && !blocked_by_maintenance
+        })
+        .map(|site| AvailableSite {
+            id: site.id.clone(),
+            name: site.name.clone(),
+            site_class_id: site.site_class_id.clone(),
+            base_rate_cents: site.base_rate_cents,
+        })
+        .collect()
+}
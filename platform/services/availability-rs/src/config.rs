@@ -0,0 +1,25 @@
+//! Environment-backed configuration.
+
+use std::env;
+
+#[derive(Debug, Clone)]
+pub struct Config {
+    pub host: String,
+    pub port: u16,
+    pub rust_log: String,
+    pub database_url: Option<String>,
+}
+
+impl Config {
+    pub fn from_env() -> Result<Self, env::VarError> {
+        Ok(Self {
+            host: env::var("HOST").unwrap_or_else(|_| "0.0.0.0".to_string()),
+            port: env::var("PORT")
+                .ok()
+                .and_then(|p| p.parse().ok())
+                .unwrap_or(8083),
+            rust_log: env::var("RUST_LOG").unwrap_or_else(|_| "info".to_string()),
+            database_url: env::var("DATABASE_URL").ok(),
+        })
+    }
+}
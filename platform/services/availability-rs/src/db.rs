@@ -0,0 +1,84 @@
+//! Database access for site, rate, and reservation lookups.
+//!
+//! Not yet wired into the HTTP handlers — today availability/pricing/deposit
+//! calculations take their inputs directly in the request body. This module
+//! exists for the reservation and scheduling services to call into directly.
+
+use chrono::NaiveDate;
+use sqlx::PgPool;
+
+use crate::availability::{ExistingReservation, MaintenanceBlock, SiteInfo};
+use crate::error::Result;
+
+pub async fn list_sites_for_campground(pool: &PgPool, campground_id: &str) -> Result<Vec<SiteInfo>> {
+    let rows: Vec<(String, String, String, Option<i32>)> = sqlx::query_as(
+        "SELECT id, name, site_class_id, base_rate_cents FROM sites WHERE campground_id = $1",
+    )
+    .bind(campground_id)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows
+        .into_iter()
+        .map(|(id, name, site_class_id, base_rate_cents)| SiteInfo {
+            id,
+            name,
+            site_class_id,
+            base_rate_cents: base_rate_cents.map(|v| v as u32),
+        })
+        .collect())
+}
+
+pub async fn list_reservations_in_range(
+    pool: &PgPool,
+    campground_id: &str,
+    arrival_date: NaiveDate,
+    departure_date: NaiveDate,
+) -> Result<Vec<ExistingReservation>> {
+    let rows: Vec<(String, NaiveDate, NaiveDate, String)> = sqlx::query_as(
+        "SELECT site_id, arrival_date, departure_date, status FROM reservations
+         WHERE campground_id = $1 AND departure_date > $2 AND arrival_date < $3",
+    )
+    .bind(campground_id)
+    .bind(arrival_date)
+    .bind(departure_date)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows
+        .into_iter()
+        .map(|(site_id, arrival_date, departure_date, status)| ExistingReservation {
+            site_id,
+            arrival_date,
+            departure_date,
+            status,
+        })
+        .collect())
+}
+
+pub async fn list_maintenance_in_range(
+    pool: &PgPool,
+    campground_id: &str,
+    start_date: NaiveDate,
+    end_date: NaiveDate,
+) -> Result<Vec<MaintenanceBlock>> {
+    let rows: Vec<(String, NaiveDate, NaiveDate, String)> = sqlx::query_as(
+        "SELECT site_id, start_date, end_date, reason FROM maintenance_blocks
+         WHERE campground_id = $1 AND end_date > $2 AND start_date < $3",
+    )
+    .bind(campground_id)
+    .bind(start_date)
+    .bind(end_date)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows
+        .into_iter()
+        .map(|(site_id, start_date, end_date, reason)| MaintenanceBlock {
+            site_id,
+            start_date,
+            end_date,
+            reason,
+        })
+        .collect())
+}
@@ -0,0 +1,43 @@
+//! Deposit calculation for a reservation.
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::{AppError, Result};
+
+#[derive(Debug, Deserialize)]
+pub struct CalculateDepositRequest {
+    pub total_cents: u32,
+    pub deposit_percent: Option<f64>,
+    pub flat_deposit_cents: Option<u32>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CalculateDepositResponse {
+    pub deposit_cents: u32,
+    pub remaining_balance_cents: u32,
+}
+
+/// A flat deposit, when set, takes precedence over a percentage of the total.
+pub fn calculate_deposit(request: &CalculateDepositRequest) -> Result<CalculateDepositResponse> {
+    let deposit_cents = if let Some(flat) = request.flat_deposit_cents {
+        flat
+    } else if let Some(percent) = request.deposit_percent {
+        if !(0.0..=100.0).contains(&percent) {
+            return Err(AppError::Validation(
+                "deposit_percent must be between 0 and 100".to_string(),
+            ));
+        }
+        ((request.total_cents as f64) * (percent / 100.0)).round() as u32
+    } else {
+        return Err(AppError::Validation(
+            "either deposit_percent or flat_deposit_cents is required".to_string(),
+        ));
+    };
+
+    let deposit_cents = deposit_cents.min(request.total_cents);
+
+    Ok(CalculateDepositResponse {
+        deposit_cents,
+        remaining_balance_cents: request.total_cents - deposit_cents,
+    })
+}
@@ -0,0 +1,29 @@
+//! Application error type and HTTP error mapping.
+
+use actix_web::{HttpResponse, ResponseError};
+
+pub type Result<T> = std::result::Result<T, AppError>;
+
+#[derive(Debug, thiserror::Error)]
+pub enum AppError {
+    #[error("validation error: {0}")]
+    Validation(String),
+
+    #[error("database error: {0}")]
+    Database(#[from] sqlx::Error),
+
+    #[error("internal error: {0}")]
+    Internal(String),
+}
+
+impl ResponseError for AppError {
+    fn error_response(&self) -> HttpResponse {
+        let body = serde_json::json!({ "error": self.to_string() });
+        match self {
+            AppError::Validation(_) => HttpResponse::BadRequest().json(body),
+            AppError::Database(_) | AppError::Internal(_) => {
+                HttpResponse::InternalServerError().json(body)
+            }
+        }
+    }
+}
@@ -0,0 +1,28 @@
+//! Revenue forecasting from historical occupancy.
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Deserialize)]
+pub struct ForecastRequest {
+    pub historical_revenue_cents: Vec<u64>,
+    pub periods_ahead: u32,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ForecastResponse {
+    pub projected_revenue_cents: Vec<u64>,
+}
+
+/// Naive moving-average forecast: project forward at the average of the
+/// trailing history.
+pub fn generate_forecast(request: &ForecastRequest) -> ForecastResponse {
+    let average = if request.historical_revenue_cents.is_empty() {
+        0
+    } else {
+        request.historical_revenue_cents.iter().sum::<u64>() / request.historical_revenue_cents.len() as u64
+    };
+
+    ForecastResponse {
+        projected_revenue_cents: vec![average; request.periods_ahead as usize],
+    }
+}
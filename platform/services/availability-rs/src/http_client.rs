@@ -0,0 +1,107 @@
+//! Outbound HTTP client that propagates W3C trace context to downstream
+//! Keepr services.
+//!
+//! Not yet wired into any handler — today pricing/availability/forecasting
+//! take their inputs directly in the request body rather than calling out
+//! to other services. This module exists so that once those calls land,
+//! traces stay connected end-to-end instead of breaking at the service
+//! boundary the way purely inbound propagation (`extract_parent_context`)
+//! does today.
+
+use opentelemetry::{global, propagation::Injector};
+use reqwest::{Method, StatusCode};
+use tracing::Instrument;
+use tracing_opentelemetry::OpenTelemetrySpanExt;
+
+use crate::error::{AppError, Result};
+
+struct HeaderInjector<'a>(&'a mut reqwest::header::HeaderMap);
+
+impl<'a> Injector for HeaderInjector<'a> {
+    fn set(&mut self, key: &str, value: String) {
+        if let (Ok(name), Ok(value)) = (
+            reqwest::header::HeaderName::from_bytes(key.as_bytes()),
+            reqwest::header::HeaderValue::from_str(&value),
+        ) {
+            self.0.insert(name, value);
+        }
+    }
+}
+
+/// Thin wrapper around `reqwest::Client` that injects the active span's
+/// trace context into every outbound request and opens a child span per
+/// call.
+#[derive(Clone)]
+pub struct TracedHttpClient {
+    inner: reqwest::Client,
+}
+
+impl TracedHttpClient {
+    pub fn new() -> Self {
+        Self {
+            inner: reqwest::Client::new(),
+        }
+    }
+
+    /// Send a request, injecting the current trace context into its
+    /// headers and recording `http.method`, `http.url`, and
+    /// `http.status_code` on a child span.
+    pub async fn send_json<T: serde::de::DeserializeOwned>(
+        &self,
+        method: Method,
+        url: &str,
+        body: Option<&impl serde::Serialize>,
+    ) -> Result<T> {
+        let span = tracing::info_span!(
+            "outbound_http_request",
+            http.method = %method,
+            http.url = %url,
+            http.status_code = tracing::field::Empty,
+        );
+        // `tracing-opentelemetry` only threads the active span's otel
+        // context through `tracing::Span`, not the `opentelemetry::Context`
+        // thread-local (that's only ever set via an explicit
+        // `Context::attach`, which nothing here calls) — so the context has
+        // to come from the current `tracing` span via `OpenTelemetrySpanExt`.
+        let otel_context = tracing::Span::current().context();
+
+        async move {
+            let mut request = self.inner.request(method, url);
+            if let Some(body) = body {
+                request = request.json(body);
+            }
+
+            let mut headers = reqwest::header::HeaderMap::new();
+            global::get_text_map_propagator(|propagator| {
+                propagator.inject_context(&otel_context, &mut HeaderInjector(&mut headers))
+            });
+            request = request.headers(headers);
+
+            let response = request.send().await.map_err(|error| {
+                AppError::Internal(format!("outbound request failed: {error}"))
+            })?;
+
+            let status = response.status();
+            tracing::Span::current().record("http.status_code", status.as_u16());
+
+            if status != StatusCode::OK {
+                return Err(AppError::Internal(format!(
+                    "downstream service returned {status}"
+                )));
+            }
+
+            response
+                .json::<T>()
+                .await
+                .map_err(|error| AppError::Internal(format!("failed to decode response: {error}")))
+        }
+        .instrument(span)
+        .await
+    }
+}
+
+impl Default for TracedHttpClient {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -15,9 +15,12 @@ use opentelemetry::{
     Context as OtelContext,
     KeyValue,
 };
+use opentelemetry_appender_tracing::layer::OpenTelemetryTracingBridge;
 use opentelemetry_otlp::WithExportConfig;
-use opentelemetry_sdk::{propagation::TraceContextPropagator, trace as sdktrace, Resource};
+use opentelemetry_sdk::{logs as sdklogs, propagation::TraceContextPropagator, trace as sdktrace, Resource};
 use std::env;
+use std::sync::Arc;
+use std::time::Instant;
 use tracing::{field, Instrument};
 use tracing_opentelemetry::OpenTelemetrySpanExt;
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
@@ -29,10 +32,21 @@ mod db;
 mod deposits;
 mod error;
 mod forecasting;
+mod http_client;
+mod metrics;
+mod otel_transport;
 mod pricing;
 
+use otel_transport::{Protocol, Signal};
+
 use config::Config;
 use error::Result;
+use metrics::Metrics;
+
+/// Application state shared across handlers.
+struct AppState {
+    metrics: Option<Metrics>,
+}
 
 #[derive(Clone)]
 struct RequestContext {
@@ -86,34 +100,75 @@ fn extract_parent_context(req: &ServiceRequest) -> OtelContext {
     global::get_text_map_propagator(|prop| prop.extract(&HeaderExtractor(req.headers())))
 }
 
-fn build_tracer(default_service_name: &str) -> Option<sdktrace::Tracer> {
-    let otel_enabled = env::var("OTEL_ENABLED").map(|value| value.to_lowercase() == "true").unwrap_or(false)
-        || env::var("OTEL_EXPORTER_OTLP_ENDPOINT").is_ok();
-    if !otel_enabled {
+/// Handles to the OTel providers installed at startup, kept around so
+/// `shutdown_otel` can force-flush and shut each one down on exit.
+#[derive(Default)]
+struct OtelProviders {
+    tracer_provider: Option<sdktrace::TracerProvider>,
+    logger_provider: Option<sdklogs::LoggerProvider>,
+}
+
+const OTEL_SHUTDOWN_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(5);
+
+/// Force-flush and shut down every installed OTel provider with a bounded
+/// timeout, so the last spans/logs/metrics before a Kubernetes pod is
+/// terminated aren't silently dropped by the batch exporters.
+async fn shutdown_otel(providers: OtelProviders, metrics: Option<metrics::Metrics>) {
+    let flush = tokio::task::spawn_blocking(move || {
+        if let Some(provider) = providers.tracer_provider {
+            if let Err(error) = provider.shutdown() {
+                eprintln!("Failed to flush OTel tracer provider: {error}");
+            }
+        }
+        if let Some(provider) = providers.logger_provider {
+            if let Err(error) = provider.shutdown() {
+                eprintln!("Failed to flush OTel logger provider: {error}");
+            }
+        }
+        if let Some(metrics) = metrics {
+            if let Err(error) = metrics.shutdown() {
+                eprintln!("Failed to flush OTel meter provider: {error}");
+            }
+        }
+    });
+
+    if tokio::time::timeout(OTEL_SHUTDOWN_TIMEOUT, flush).await.is_err() {
+        eprintln!("Timed out flushing OTel providers on shutdown");
+    }
+}
+
+fn build_tracer(default_service_name: &str) -> Option<(sdktrace::Tracer, sdktrace::TracerProvider)> {
+    if !otel_enabled() {
         return None;
     }
 
-    let endpoint = match env::var("OTEL_EXPORTER_OTLP_ENDPOINT") {
-        Ok(value) => value,
-        Err(_) => {
-            eprintln!("OTEL_ENABLED is set but OTEL_EXPORTER_OTLP_ENDPOINT is missing; skipping OTel.");
+    let endpoint = match otel_transport::endpoint_for(Signal::Traces) {
+        Some(value) => value,
+        None => {
+            eprintln!("OTEL_ENABLED is set but no OTLP endpoint is configured; skipping OTel.");
             return None;
         }
     };
 
     let service_name = env::var("OTEL_SERVICE_NAME").unwrap_or_else(|_| default_service_name.to_string());
     global::set_text_map_propagator(TraceContextPropagator::new());
-    let provider = opentelemetry_otlp::new_pipeline()
+    let pipeline = opentelemetry_otlp::new_pipeline()
         .tracing()
-        .with_exporter(opentelemetry_otlp::new_exporter().http().with_endpoint(endpoint))
-        .with_trace_config(sdktrace::Config::default().with_resource(Resource::new(vec![KeyValue::new("service.name", service_name.clone())])))
-        .install_batch(opentelemetry_sdk::runtime::Tokio);
+        .with_trace_config(sdktrace::Config::default().with_resource(Resource::new(vec![KeyValue::new("service.name", service_name.clone())])));
+    let provider = match otel_transport::protocol_from_env() {
+        Protocol::HttpProtobuf => pipeline
+            .with_exporter(otel_transport::http_builder(Signal::Traces, &endpoint))
+            .install_batch(opentelemetry_sdk::runtime::Tokio),
+        Protocol::Grpc => pipeline
+            .with_exporter(otel_transport::tonic_builder(Signal::Traces, &endpoint))
+            .install_batch(opentelemetry_sdk::runtime::Tokio),
+    };
 
     match provider {
         Ok(provider) => {
             let tracer = provider.tracer(service_name);
-            global::set_tracer_provider(provider);
-            Some(tracer)
+            global::set_tracer_provider(provider.clone());
+            Some((tracer, provider))
         }
         Err(error) => {
             eprintln!("Failed to initialize OTel tracer: {error}");
@@ -122,15 +177,60 @@ fn build_tracer(default_service_name: &str) -> Option<sdktrace::Tracer> {
     }
 }
 
-fn init_tracing(rust_log: &str, default_service_name: &str) {
+fn otel_enabled() -> bool {
+    env::var("OTEL_ENABLED").map(|value| value.to_lowercase() == "true").unwrap_or(false)
+        || env::var("OTEL_EXPORTER_OTLP_ENDPOINT").is_ok()
+}
+
+/// Build an OTLP logger provider so `tracing` events are exported as log
+/// records alongside the spans built by `build_tracer`, correlated via the
+/// active trace/span ids. Gated behind the same `OTEL_ENABLED` switch.
+fn build_logger_provider(default_service_name: &str) -> Option<sdklogs::LoggerProvider> {
+    if !otel_enabled() {
+        return None;
+    }
+
+    let endpoint = otel_transport::endpoint_for(Signal::Logs)?;
+    let service_name = env::var("OTEL_SERVICE_NAME").unwrap_or_else(|_| default_service_name.to_string());
+
+    let pipeline = opentelemetry_otlp::new_pipeline()
+        .logging()
+        .with_log_config(sdklogs::Config::default().with_resource(Resource::new(vec![KeyValue::new("service.name", service_name)])));
+    let provider = match otel_transport::protocol_from_env() {
+        Protocol::HttpProtobuf => pipeline
+            .with_exporter(otel_transport::http_builder(Signal::Logs, &endpoint))
+            .install_batch(opentelemetry_sdk::runtime::Tokio),
+        Protocol::Grpc => pipeline
+            .with_exporter(otel_transport::tonic_builder(Signal::Logs, &endpoint))
+            .install_batch(opentelemetry_sdk::runtime::Tokio),
+    };
+
+    match provider {
+        Ok(provider) => Some(provider),
+        Err(error) => {
+            eprintln!("Failed to initialize OTel logger: {error}");
+            None
+        }
+    }
+}
+
+fn init_tracing(rust_log: &str, default_service_name: &str) -> OtelProviders {
     let base = tracing_subscriber::registry().with(tracing_subscriber::EnvFilter::new(rust_log));
     let fmt_layer = tracing_subscriber::fmt::layer();
+    let logger_provider = build_logger_provider(default_service_name);
+    let log_layer = logger_provider.as_ref().map(OpenTelemetryTracingBridge::new);
+    let tracer_provider_pair = build_tracer(default_service_name);
 
-    if let Some(tracer) = build_tracer(default_service_name) {
-        let otel_layer = tracing_opentelemetry::layer().with_tracer(tracer);
-        base.with(fmt_layer).with(otel_layer).init();
+    if let Some((tracer, _)) = &tracer_provider_pair {
+        let otel_layer = tracing_opentelemetry::layer().with_tracer(tracer.clone());
+        base.with(fmt_layer).with(otel_layer).with(log_layer).init();
     } else {
-        base.with(fmt_layer).init();
+        base.with(fmt_layer).with(log_layer).init();
+    }
+
+    OtelProviders {
+        tracer_provider: tracer_provider_pair.map(|(_, provider)| provider),
+        logger_provider,
     }
 }
 
@@ -150,6 +250,18 @@ async fn ready() -> HttpResponse {
     health().await
 }
 
+/// Prometheus scrape endpoint. Returns an empty 404 when metrics export is
+/// disabled (`OTEL_METRICS_ENABLED` unset), so the route is safe to leave
+/// mounted in every environment.
+async fn metrics_handler(state: web::Data<AppState>) -> HttpResponse {
+    match state.metrics.as_ref().and_then(|m| m.render_prometheus()) {
+        Some(body) => HttpResponse::Ok()
+            .content_type("text/plain; version=0.0.4")
+            .body(body),
+        None => HttpResponse::NotFound().finish(),
+    }
+}
+
 // ============================================================================
 // Pricing Handlers
 // ============================================================================
@@ -288,19 +400,26 @@ async fn main() -> std::io::Result<()> {
     let config = Config::from_env().expect("Failed to load configuration");
 
     // Initialize tracing
-    init_tracing(&config.rust_log, "keepr-availability");
+    let otel_providers = init_tracing(&config.rust_log, "keepr-availability");
 
     tracing::info!("Starting Availability Calculator service");
 
+    let metrics = Arc::new(Metrics::init("keepr-availability"));
+    let metrics_for_shutdown = (*metrics).clone();
+
     let bind_addr = format!("{}:{}", config.host, config.port);
     tracing::info!("Listening on {}", bind_addr);
 
     // Start HTTP server
     HttpServer::new(move || {
+        let metrics_for_fn = metrics.clone();
         App::new()
+            .app_data(web::Data::new(AppState {
+                metrics: (*metrics).clone(),
+            }))
             .wrap(middleware::Logger::default())
             .wrap(middleware::Compress::default())
-            .wrap_fn(|req, srv| {
+            .wrap_fn(move |req, srv| {
                 let context = build_request_context(&req);
                 let (trace_id, span_id) = parse_traceparent(context.traceparent.as_deref());
                 let tracestate_present = context.tracestate.is_some();
@@ -311,7 +430,9 @@ async fn main() -> std::io::Result<()> {
                     trace_id = field::Empty,
                     span_id = field::Empty,
                     tracestate_present = tracestate_present,
-                    method = %req.method(),
+                    "http.method" = %req.method(),
+                    "http.route" = field::Empty,
+                    "http.status_code" = field::Empty,
                     path = %req.path()
                 );
                 let parent_context = extract_parent_context(&req);
@@ -324,9 +445,44 @@ async fn main() -> std::io::Result<()> {
                 if let Some(value) = span_id.as_deref() {
                     span.record("span_id", value);
                 }
+
+                let metrics = metrics_for_fn.clone();
+                let method = req.method().to_string();
+                let start = Instant::now();
+                if let Some(m) = metrics.as_ref() {
+                    m.in_flight.add(1, &[KeyValue::new("http.method", method.clone())]);
+                }
+
                 let fut = srv.call(req);
                 async move {
-                    let mut res = fut.await?;
+                    let result = fut.await;
+                    let route = result
+                        .as_ref()
+                        .ok()
+                        .and_then(|res| res.request().match_pattern())
+                        .unwrap_or_else(|| "unmatched".to_string());
+                    let status = result.as_ref().map(|res| res.status().as_u16()).unwrap_or(500);
+
+                    let current_span = tracing::Span::current();
+                    current_span.record("http.route", route.as_str());
+                    current_span.record("http.status_code", status);
+                    if status >= 500 {
+                        current_span.set_status(opentelemetry::trace::Status::error(format!(
+                            "HTTP {status}"
+                        )));
+                    }
+
+                    if let Some(m) = metrics.as_ref() {
+                        let attributes = [
+                            KeyValue::new("http.method", method.clone()),
+                            KeyValue::new("http.route", route),
+                            KeyValue::new("http.status_code", status as i64),
+                        ];
+                        m.requests_total.add(1, &attributes);
+                        m.request_duration_seconds.record(start.elapsed().as_secs_f64(), &attributes);
+                        m.in_flight.add(-1, &[KeyValue::new("http.method", method)]);
+                    }
+                    let mut res = result?;
                     res.headers_mut().insert(
                         HeaderName::from_static("x-request-id"),
                         HeaderValue::from_str(&context.request_id).unwrap(),
@@ -338,6 +494,7 @@ async fn main() -> std::io::Result<()> {
             // Health check
             .route("/health", web::get().to(health))
             .route("/ready", web::get().to(ready))
+            .route("/metrics", web::get().to(metrics_handler))
             // Pricing
             .route("/api/pricing/evaluate", web::post().to(evaluate_pricing))
             // Availability
@@ -349,5 +506,12 @@ async fn main() -> std::io::Result<()> {
     })
     .bind(&bind_addr)?
     .run()
-    .await
+    .await?;
+
+    // actix-web's default signal handling already stops accepting new
+    // connections and drains in-flight requests before `.run()` resolves;
+    // flush the OTel providers afterward so their last export isn't lost.
+    shutdown_otel(otel_providers, metrics_for_shutdown).await;
+
+    Ok(())
 }
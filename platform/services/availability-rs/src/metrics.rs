@@ -0,0 +1,122 @@
+//! RED-style metrics: an OTLP meter provider alongside the existing tracer,
+//! plus an `opentelemetry-prometheus` exporter behind `/metrics` so
+//! deployments without an OTLP collector can still scrape.
+//!
+//! Metrics export is opt-in via `OTEL_METRICS_ENABLED` so a trace-only setup
+//! (the default before this change) keeps working unmodified.
+
+use std::env;
+
+use opentelemetry::{global, metrics::Meter, KeyValue};
+use opentelemetry_sdk::{metrics::SdkMeterProvider, runtime, Resource};
+use prometheus::{Encoder, Registry, TextEncoder};
+
+use crate::otel_transport::{self, Protocol, Signal};
+
+#[derive(Clone)]
+pub struct Metrics {
+    pub requests_total: opentelemetry::metrics::Counter<u64>,
+    pub in_flight: opentelemetry::metrics::UpDownCounter<i64>,
+    pub request_duration_seconds: opentelemetry::metrics::Histogram<f64>,
+    pub prometheus_registry: Option<Registry>,
+    meter_provider: SdkMeterProvider,
+}
+
+fn metrics_enabled() -> bool {
+    env::var("OTEL_METRICS_ENABLED")
+        .map(|v| v.to_lowercase() == "true")
+        .unwrap_or(false)
+}
+
+fn build_meter(service_name: &str) -> (Meter, Option<Registry>, SdkMeterProvider) {
+    let resource = Resource::new(vec![KeyValue::new("service.name", service_name.to_string())]);
+    let mut provider_builder = SdkMeterProvider::builder().with_resource(resource.clone());
+
+    // Always register the Prometheus exporter when metrics are enabled at
+    // all, so a deployment with no collector still gets a working scrape.
+    let registry = Registry::new();
+    let prometheus_exporter = opentelemetry_prometheus::exporter()
+        .with_registry(registry.clone())
+        .build();
+
+    let prometheus_reader = match prometheus_exporter {
+        Ok(exporter) => Some(exporter),
+        Err(error) => {
+            eprintln!("Failed to build Prometheus exporter: {error}");
+            None
+        }
+    };
+
+    if let Some(reader) = prometheus_reader {
+        provider_builder = provider_builder.with_reader(reader);
+    }
+
+    if let Some(endpoint) = otel_transport::endpoint_for(Signal::Metrics) {
+        let temporality = Box::new(opentelemetry_sdk::metrics::reader::DefaultTemporalitySelector::new());
+        let aggregation = Box::new(opentelemetry_sdk::metrics::reader::DefaultAggregationSelector::new());
+        let otlp_exporter = match otel_transport::protocol_from_env() {
+            Protocol::HttpProtobuf => otel_transport::http_builder(Signal::Metrics, &endpoint)
+                .build_metrics_exporter(temporality, aggregation),
+            Protocol::Grpc => otel_transport::tonic_builder(Signal::Metrics, &endpoint)
+                .build_metrics_exporter(temporality, aggregation),
+        };
+        match otlp_exporter {
+            Ok(exporter) => {
+                let reader = opentelemetry_sdk::metrics::PeriodicReader::builder(exporter, runtime::Tokio).build();
+                provider_builder = provider_builder.with_reader(reader);
+            }
+            Err(error) => eprintln!("Failed to build OTLP metrics exporter: {error}"),
+        }
+    }
+
+    let provider = provider_builder.build();
+    global::set_meter_provider(provider.clone());
+    let meter = provider.meter(service_name.to_string());
+
+    (meter, Some(registry), provider)
+}
+
+impl Metrics {
+    pub fn init(default_service_name: &str) -> Option<Self> {
+        if !metrics_enabled() {
+            return None;
+        }
+
+        let service_name = env::var("OTEL_SERVICE_NAME").unwrap_or_else(|_| default_service_name.to_string());
+        let (meter, prometheus_registry, meter_provider) = build_meter(&service_name);
+
+        Some(Self {
+            requests_total: meter
+                .u64_counter("http.server.requests")
+                .with_description("Total HTTP requests handled")
+                .init(),
+            in_flight: meter
+                .i64_up_down_counter("http.server.active_requests")
+                .with_description("In-flight HTTP requests")
+                .init(),
+            request_duration_seconds: meter
+                .f64_histogram("http.server.duration")
+                .with_description("HTTP request duration in seconds")
+                .init(),
+            prometheus_registry,
+            meter_provider,
+        })
+    }
+
+    /// Force-flush and shut down the meter provider, e.g. on graceful
+    /// shutdown so the last batch of metrics isn't dropped.
+    pub fn shutdown(self) -> opentelemetry::metrics::Result<()> {
+        self.meter_provider.shutdown()
+    }
+
+    /// Render the current Prometheus exposition-format text, if the
+    /// Prometheus exporter is registered.
+    pub fn render_prometheus(&self) -> Option<String> {
+        let registry = self.prometheus_registry.as_ref()?;
+        let encoder = TextEncoder::new();
+        let metric_families = registry.gather();
+        let mut buffer = Vec::new();
+        encoder.encode(&metric_families, &mut buffer).ok()?;
+        String::from_utf8(buffer).ok()
+    }
+}
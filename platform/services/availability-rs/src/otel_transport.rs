@@ -0,0 +1,110 @@
+//! Shared OTLP transport selection for the tracer, meter, and logger
+//! pipelines in `main.rs` and `metrics.rs`.
+//!
+//! Honors the standard OpenTelemetry SDK environment variables so this
+//! service can point at either an HTTP/protobuf or a gRPC collector, and so
+//! each signal can be routed to a different endpoint if needed:
+//!
+//! - `OTEL_EXPORTER_OTLP_PROTOCOL` — `http/protobuf` (default) or `grpc`
+//! - `OTEL_EXPORTER_OTLP_ENDPOINT` — default endpoint for all signals
+//! - `OTEL_EXPORTER_OTLP_TRACES_ENDPOINT` / `_METRICS_ENDPOINT` / `_LOGS_ENDPOINT`
+//!   — per-signal overrides
+//! - `OTEL_EXPORTER_OTLP_HEADERS` — comma-separated `key=value` pairs sent
+//!   with every export request (e.g. collector auth tokens)
+
+use std::env;
+
+use opentelemetry_otlp::{
+    HttpExporterBuilder, TonicExporterBuilder, WithExportConfig,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Protocol {
+    HttpProtobuf,
+    Grpc,
+}
+
+/// Which telemetry signal an exporter is being built for; used to resolve
+/// the per-signal endpoint override.
+#[derive(Debug, Clone, Copy)]
+pub enum Signal {
+    Traces,
+    Metrics,
+    Logs,
+}
+
+impl Signal {
+    fn endpoint_env_var(self) -> &'static str {
+        match self {
+            Signal::Traces => "OTEL_EXPORTER_OTLP_TRACES_ENDPOINT",
+            Signal::Metrics => "OTEL_EXPORTER_OTLP_METRICS_ENDPOINT",
+            Signal::Logs => "OTEL_EXPORTER_OTLP_LOGS_ENDPOINT",
+        }
+    }
+}
+
+pub fn protocol_from_env() -> Protocol {
+    match env::var("OTEL_EXPORTER_OTLP_PROTOCOL").as_deref() {
+        Ok("grpc") => Protocol::Grpc,
+        _ => Protocol::HttpProtobuf,
+    }
+}
+
+/// Resolve the endpoint for a given signal: the per-signal override if set,
+/// otherwise the shared `OTEL_EXPORTER_OTLP_ENDPOINT`.
+pub fn endpoint_for(signal: Signal) -> Option<String> {
+    env::var(signal.endpoint_env_var())
+        .ok()
+        .or_else(|| env::var("OTEL_EXPORTER_OTLP_ENDPOINT").ok())
+}
+
+/// Parse `OTEL_EXPORTER_OTLP_HEADERS` (`key1=value1,key2=value2`) into pairs.
+pub fn headers_from_env() -> Vec<(String, String)> {
+    let raw = match env::var("OTEL_EXPORTER_OTLP_HEADERS") {
+        Ok(value) => value,
+        Err(_) => return Vec::new(),
+    };
+
+    raw.split(',')
+        .filter_map(|pair| {
+            let (key, value) = pair.split_once('=')?;
+            let key = key.trim();
+            let value = value.trim();
+            if key.is_empty() {
+                return None;
+            }
+            Some((key.to_string(), value.to_string()))
+        })
+        .collect()
+}
+
+/// Build an HTTP/protobuf exporter builder for the given signal, applying
+/// the resolved endpoint and any configured headers.
+pub fn http_builder(signal: Signal, endpoint: &str) -> HttpExporterBuilder {
+    let mut builder = opentelemetry_otlp::new_exporter().http().with_endpoint(endpoint);
+    let headers = headers_from_env();
+    if !headers.is_empty() {
+        builder = builder.with_headers(headers.into_iter().collect());
+    }
+    let _ = signal;
+    builder
+}
+
+/// Build a tonic-based gRPC exporter builder for the given signal, applying
+/// the resolved endpoint and any configured headers as gRPC metadata.
+pub fn tonic_builder(signal: Signal, endpoint: &str) -> TonicExporterBuilder {
+    let mut metadata = tonic::metadata::MetadataMap::new();
+    for (key, value) in headers_from_env() {
+        if let (Ok(key), Ok(value)) = (
+            tonic::metadata::MetadataKey::from_bytes(key.as_bytes()),
+            value.parse(),
+        ) {
+            metadata.insert(key, value);
+        }
+    }
+    let _ = signal;
+    opentelemetry_otlp::new_exporter()
+        .tonic()
+        .with_endpoint(endpoint)
+        .with_metadata(metadata)
+}
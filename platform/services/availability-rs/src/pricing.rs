@@ -0,0 +1,57 @@
+//! Pricing evaluation for a reservation.
+
+use chrono::{Datelike, NaiveDate};
+use serde::{Deserialize, Serialize};
+
+use crate::error::{AppError, Result};
+
+#[derive(Debug, Deserialize)]
+pub struct EvaluatePricingRequest {
+    pub site_class_id: String,
+    pub base_rate_cents: u32,
+    pub arrival_date: NaiveDate,
+    pub departure_date: NaiveDate,
+    pub weekend_multiplier: Option<f64>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct EvaluatePricingResponse {
+    pub site_class_id: String,
+    pub nights: i64,
+    pub total_cents: u32,
+    pub nightly_breakdown_cents: Vec<u32>,
+}
+
+/// Evaluate the nightly rate schedule for a stay, applying a weekend
+/// multiplier (Fri/Sat nights) on top of the class base rate.
+pub fn evaluate_pricing(request: &EvaluatePricingRequest) -> Result<EvaluatePricingResponse> {
+    let nights = (request.departure_date - request.arrival_date).num_days();
+    if nights <= 0 {
+        return Err(AppError::Validation(
+            "departure_date must be after arrival_date".to_string(),
+        ));
+    }
+
+    let multiplier = request.weekend_multiplier.unwrap_or(1.0);
+    let mut nightly_breakdown_cents = Vec::with_capacity(nights as usize);
+    let mut total_cents = 0u32;
+
+    for offset in 0..nights {
+        let night = request.arrival_date + chrono::Duration::days(offset);
+        let is_weekend = matches!(night.weekday(), chrono::Weekday::Fri | chrono::Weekday::Sat);
+        let rate = if is_weekend {
+            ((request.base_rate_cents as f64) * multiplier).round() as u32
+        } else {
+            request.base_rate_cents
+        };
+        nightly_breakdown_cents.push(rate);
+        total_cents += rate;
+    }
+
+    Ok(EvaluatePricingResponse {
+        site_class_id: request.site_class_id.clone(),
+        nights,
+        total_cents,
+        nightly_breakdown_cents,
+    })
+}
@@ -0,0 +1,146 @@
+//! Structured analytics event stream.
+//!
+//! Emits one record per payment operation and per HTTP request to an
+//! append-only `payment_events` table, so finance/ops has a queryable event
+//! log instead of only ephemeral `tracing` output. The hot request path only
+//! ever does a non-blocking channel send; a background task batches and
+//! flushes to Postgres.
+
+use serde::Serialize;
+use sqlx::PgPool;
+use tokio::sync::mpsc;
+
+/// Metadata values are attacker- or partner-influenced (webhook payloads,
+/// request bodies), so serialization must never recurse unbounded: cap
+/// nesting depth and flatten anything deeper to an opaque string rather than
+/// re-entering the formatter. Keys are not checked against any known set;
+/// every key is kept and its value sanitized the same way.
+const MAX_METADATA_DEPTH: usize = 4;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct AnalyticsEvent {
+    pub trace_id: Option<String>,
+    pub kind: String,
+    pub provider: Option<String>,
+    pub amount_cents: Option<i64>,
+    pub currency: Option<String>,
+    pub platform_fee_cents: Option<i64>,
+    pub gateway_fee_cents: Option<i64>,
+    pub connected_account_id: Option<String>,
+    pub http_method: Option<String>,
+    pub http_route: Option<String>,
+    pub http_status: Option<u16>,
+    pub latency_ms: Option<u64>,
+    pub outcome: String,
+    pub metadata: serde_json::Value,
+}
+
+/// Recursively sanitize a JSON value, capping depth so a deeply nested or
+/// cyclic-looking payload can't blow the stack while we format it.
+pub fn sanitize_metadata(value: &serde_json::Value, depth: usize) -> serde_json::Value {
+    if depth >= MAX_METADATA_DEPTH {
+        return serde_json::Value::String(truncated_opaque_string(value));
+    }
+
+    match value {
+        serde_json::Value::Object(map) => {
+            let mut sanitized = serde_json::Map::with_capacity(map.len());
+            for (key, v) in map {
+                sanitized.insert(key.clone(), sanitize_metadata(v, depth + 1));
+            }
+            serde_json::Value::Object(sanitized)
+        }
+        serde_json::Value::Array(items) => serde_json::Value::Array(
+            items.iter().map(|v| sanitize_metadata(v, depth + 1)).collect(),
+        ),
+        other => other.clone(),
+    }
+}
+
+fn truncated_opaque_string(value: &serde_json::Value) -> String {
+    let rendered = value.to_string();
+    const MAX_LEN: usize = 256;
+    if rendered.len() > MAX_LEN {
+        format!("{}...<truncated>", &rendered[..MAX_LEN])
+    } else {
+        rendered
+    }
+}
+
+/// Bounded background sink. `record` never blocks the request path: once the
+/// channel is full, events are dropped rather than backing up the caller.
+#[derive(Clone)]
+pub struct AnalyticsSink {
+    sender: mpsc::Sender<AnalyticsEvent>,
+}
+
+impl AnalyticsSink {
+    pub fn new(pool: PgPool, channel_capacity: usize, batch_size: usize) -> Self {
+        let (sender, receiver) = mpsc::channel(channel_capacity);
+        tokio::spawn(run_flush_loop(pool, receiver, batch_size));
+        Self { sender }
+    }
+
+    pub fn record(&self, event: AnalyticsEvent) {
+        if self.sender.try_send(event).is_err() {
+            tracing::warn!("analytics channel full or closed; dropping event");
+        }
+    }
+}
+
+async fn run_flush_loop(pool: PgPool, mut receiver: mpsc::Receiver<AnalyticsEvent>, batch_size: usize) {
+    let mut batch = Vec::with_capacity(batch_size);
+    loop {
+        let received = tokio::time::timeout(std::time::Duration::from_secs(1), receiver.recv()).await;
+        match received {
+            Ok(Some(event)) => {
+                batch.push(event);
+                if batch.len() >= batch_size {
+                    flush_batch(&pool, &mut batch).await;
+                }
+            }
+            Ok(None) => {
+                flush_batch(&pool, &mut batch).await;
+                break;
+            }
+            Err(_elapsed) => {
+                flush_batch(&pool, &mut batch).await;
+            }
+        }
+    }
+}
+
+async fn flush_batch(pool: &PgPool, batch: &mut Vec<AnalyticsEvent>) {
+    if batch.is_empty() {
+        return;
+    }
+    for event in batch.drain(..) {
+        let metadata = sanitize_metadata(&event.metadata, 0);
+        if let Err(error) = sqlx::query(
+            "INSERT INTO payment_events
+                (trace_id, kind, provider, amount_cents, currency, platform_fee_cents,
+                 gateway_fee_cents, connected_account_id, http_method, http_route,
+                 http_status, latency_ms, outcome, metadata)
+             VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14)",
+        )
+        .bind(&event.trace_id)
+        .bind(&event.kind)
+        .bind(&event.provider)
+        .bind(event.amount_cents)
+        .bind(&event.currency)
+        .bind(event.platform_fee_cents)
+        .bind(event.gateway_fee_cents)
+        .bind(&event.connected_account_id)
+        .bind(&event.http_method)
+        .bind(&event.http_route)
+        .bind(event.http_status.map(i32::from))
+        .bind(event.latency_ms.map(|v| v as i64))
+        .bind(&event.outcome)
+        .bind(metadata)
+        .execute(pool)
+        .await
+        {
+            tracing::error!(%error, "failed to flush analytics event");
+        }
+    }
+}
@@ -0,0 +1,53 @@
+//! Environment-backed configuration.
+
+use std::env;
+
+#[derive(Debug, Clone)]
+pub struct Config {
+    pub host: String,
+    pub port: u16,
+    pub rust_log: String,
+    pub database_url: String,
+    pub stripe_secret_key: String,
+    pub stripe_webhook_secret: String,
+    pub platform_fee_cents: u32,
+    pub payout_drift_threshold_cents: u32,
+    pub event_bus_backend: String,
+    pub redis_url: Option<String>,
+    pub stripe_max_retry_attempts: u32,
+    pub stripe_retry_base_delay_ms: u64,
+}
+
+impl Config {
+    pub fn from_env() -> Result<Self, env::VarError> {
+        Ok(Self {
+            host: env::var("HOST").unwrap_or_else(|_| "0.0.0.0".to_string()),
+            port: env::var("PORT")
+                .ok()
+                .and_then(|p| p.parse().ok())
+                .unwrap_or(8082),
+            rust_log: env::var("RUST_LOG").unwrap_or_else(|_| "info".to_string()),
+            database_url: env::var("DATABASE_URL")?,
+            stripe_secret_key: env::var("STRIPE_SECRET_KEY")?,
+            stripe_webhook_secret: env::var("STRIPE_WEBHOOK_SECRET")?,
+            platform_fee_cents: env::var("PLATFORM_FEE_CENTS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(0),
+            payout_drift_threshold_cents: env::var("PAYOUT_DRIFT_THRESHOLD_CENTS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(100),
+            event_bus_backend: env::var("EVENT_BUS_BACKEND").unwrap_or_else(|_| "local".to_string()),
+            redis_url: env::var("REDIS_URL").ok(),
+            stripe_max_retry_attempts: env::var("STRIPE_MAX_RETRY_ATTEMPTS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(4),
+            stripe_retry_base_delay_ms: env::var("STRIPE_RETRY_BASE_DELAY_MS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(200),
+        })
+    }
+}
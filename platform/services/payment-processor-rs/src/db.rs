@@ -0,0 +1,133 @@
+//! Database access for payment and reconciliation records.
+
+use sqlx::PgPool;
+
+use crate::error::Result;
+use crate::reconciliation::LedgerEntry;
+
+/// Look up the Stripe connected-account id for a campground.
+pub async fn get_campground_stripe_account(
+    pool: &PgPool,
+    campground_id: &str,
+) -> Result<Option<String>> {
+    let row: Option<(String,)> = sqlx::query_as(
+        "SELECT stripe_account_id FROM campgrounds WHERE id = $1 AND stripe_account_id IS NOT NULL",
+    )
+    .bind(campground_id)
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(row.map(|(account_id,)| account_id))
+}
+
+/// Check whether a webhook event has already been processed, without
+/// recording anything. Callers should only mark an event processed once its
+/// side effects (DB upserts, event-bus publishes) have actually succeeded —
+/// see `mark_event_processed`.
+pub async fn is_event_processed(pool: &PgPool, event_id: &str) -> Result<bool> {
+    let row: (bool,) = sqlx::query_as(
+        "SELECT EXISTS(SELECT 1 FROM processed_webhook_events WHERE event_id = $1)",
+    )
+    .bind(event_id)
+    .fetch_one(pool)
+    .await?;
+
+    Ok(row.0)
+}
+
+/// Record that a webhook event has been processed. Returns `false` (instead
+/// of erroring) when the event id was already recorded, so callers can treat
+/// Stripe's at-least-once redelivery as a no-op.
+pub async fn mark_event_processed(pool: &PgPool, event_id: &str, event_type: &str) -> Result<bool> {
+    let result = sqlx::query(
+        "INSERT INTO processed_webhook_events (event_id, event_type) VALUES ($1, $2)
+         ON CONFLICT (event_id) DO NOTHING",
+    )
+    .bind(event_id)
+    .bind(event_type)
+    .execute(pool)
+    .await?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+/// Upsert a payment record keyed by payment-intent id, so redelivery of the
+/// same webhook event updates the row instead of duplicating it.
+#[allow(clippy::too_many_arguments)]
+pub async fn insert_or_update_payment(
+    pool: &PgPool,
+    payment_intent_id: &str,
+    charge_id: Option<&str>,
+    campground_id: Option<&str>,
+    status: &str,
+    amount_cents: i64,
+    amount_refunded_cents: i64,
+) -> Result<()> {
+    sqlx::query(
+        "INSERT INTO payments (payment_intent_id, charge_id, campground_id, status, amount_cents, amount_refunded_cents)
+         VALUES ($1, $2, $3, $4, $5, $6)
+         ON CONFLICT (payment_intent_id) DO UPDATE SET
+             charge_id = COALESCE(EXCLUDED.charge_id, payments.charge_id),
+             status = EXCLUDED.status,
+             amount_cents = EXCLUDED.amount_cents,
+             amount_refunded_cents = EXCLUDED.amount_refunded_cents,
+             updated_at = now()",
+    )
+    .bind(payment_intent_id)
+    .bind(charge_id)
+    .bind(campground_id)
+    .bind(status)
+    .bind(amount_cents)
+    .bind(amount_refunded_cents)
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+/// Persist a payout we initiated so the later `payout.paid`/`payout.updated`
+/// webhook can be matched against it during reconciliation.
+pub async fn insert_initiated_payout(
+    pool: &PgPool,
+    payout_id: &str,
+    connected_account_id: &str,
+    amount_cents: i64,
+    currency: &str,
+    status: &str,
+    arrival_date: Option<i64>,
+    idempotency_key: &str,
+) -> Result<()> {
+    sqlx::query(
+        "INSERT INTO payouts (payout_id, connected_account_id, amount_cents, currency, status, arrival_date, idempotency_key)
+         VALUES ($1, $2, $3, $4, $5, $6, $7)
+         ON CONFLICT (payout_id) DO UPDATE SET status = EXCLUDED.status, arrival_date = EXCLUDED.arrival_date",
+    )
+    .bind(payout_id)
+    .bind(connected_account_id)
+    .bind(amount_cents)
+    .bind(currency)
+    .bind(status)
+    .bind(arrival_date)
+    .bind(idempotency_key)
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+/// Insert a single ledger entry row.
+pub async fn insert_ledger_entry(pool: &PgPool, entry: &LedgerEntry) -> Result<()> {
+    sqlx::query(
+        "INSERT INTO ledger_entries (payout_id, campground_id, account, amount_cents, entry_type)
+         VALUES ($1, $2, $3, $4, $5)",
+    )
+    .bind(&entry.payout_id)
+    .bind(&entry.campground_id)
+    .bind(&entry.account)
+    .bind(entry.amount_cents)
+    .bind(&entry.entry_type)
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
@@ -0,0 +1,129 @@
+//! Domain event bus.
+//!
+//! Payment ingestion publishes events here instead of logging TODOs; other
+//! Campreserv services (reconciliation workers, notifications) subscribe
+//! through whichever `EventBus` implementation is configured.
+
+use async_trait::async_trait;
+use serde::Serialize;
+
+use crate::error::{AppError, Result};
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "type")]
+pub enum DomainEvent {
+    PaymentSucceeded {
+        payment_intent_id: String,
+        campground_id: Option<String>,
+        amount_cents: u64,
+    },
+    PaymentFailed {
+        payment_intent_id: String,
+        reason: Option<String>,
+    },
+    ChargeRefunded {
+        charge_id: String,
+        amount_refunded_cents: u64,
+    },
+    PayoutUpdated {
+        payout_id: String,
+        status: String,
+    },
+    DisputeCreated {
+        dispute_id: String,
+        amount_cents: u64,
+        reason: String,
+    },
+    DriftDetected {
+        payout_id: String,
+        drift_cents: i64,
+        severity: String,
+    },
+}
+
+impl DomainEvent {
+    /// The event-type name used as the Redis stream key suffix / log field.
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            DomainEvent::PaymentSucceeded { .. } => "payment_succeeded",
+            DomainEvent::PaymentFailed { .. } => "payment_failed",
+            DomainEvent::ChargeRefunded { .. } => "charge_refunded",
+            DomainEvent::PayoutUpdated { .. } => "payout_updated",
+            DomainEvent::DisputeCreated { .. } => "dispute_created",
+            DomainEvent::DriftDetected { .. } => "drift_detected",
+        }
+    }
+}
+
+#[async_trait]
+pub trait EventBus: Send + Sync {
+    async fn publish(&self, event: DomainEvent) -> Result<()>;
+}
+
+/// In-process event bus for a single instance; subscribers get a receiver off
+/// the broadcast channel. Events are dropped if no one is listening.
+pub struct LocalEventBus {
+    sender: tokio::sync::broadcast::Sender<DomainEvent>,
+}
+
+impl LocalEventBus {
+    pub fn new(capacity: usize) -> Self {
+        let (sender, _receiver) = tokio::sync::broadcast::channel(capacity);
+        Self { sender }
+    }
+
+    pub fn subscribe(&self) -> tokio::sync::broadcast::Receiver<DomainEvent> {
+        self.sender.subscribe()
+    }
+}
+
+#[async_trait]
+impl EventBus for LocalEventBus {
+    async fn publish(&self, event: DomainEvent) -> Result<()> {
+        // No subscribers is not an error; it just means nothing is listening yet.
+        let _ = self.sender.send(event);
+        Ok(())
+    }
+}
+
+/// Publishes each event as JSON to a per-event-type Redis stream
+/// (`{stream_prefix}:{type_name}`) via `XADD`, so external services can consume with
+/// consumer groups.
+pub struct RedisEventBus {
+    client: redis::Client,
+    stream_prefix: String,
+}
+
+impl RedisEventBus {
+    pub fn new(redis_url: &str, stream_prefix: impl Into<String>) -> Result<Self> {
+        let client = redis::Client::open(redis_url)
+            .map_err(|e| AppError::Internal(format!("invalid redis url: {e}")))?;
+        Ok(Self {
+            client,
+            stream_prefix: stream_prefix.into(),
+        })
+    }
+}
+
+#[async_trait]
+impl EventBus for RedisEventBus {
+    async fn publish(&self, event: DomainEvent) -> Result<()> {
+        use redis::AsyncCommands;
+
+        let payload = serde_json::to_string(&event)
+            .map_err(|e| AppError::Internal(format!("failed to serialize event: {e}")))?;
+        let stream_key = format!("{}:{}", self.stream_prefix, event.type_name());
+
+        let mut conn = self
+            .client
+            .get_multiplexed_async_connection()
+            .await
+            .map_err(|e| AppError::Internal(format!("redis connection failed: {e}")))?;
+
+        conn.xadd::<_, _, _, _, ()>(stream_key, "*", &[("payload", payload)])
+            .await
+            .map_err(|e| AppError::Internal(format!("XADD failed: {e}")))?;
+
+        Ok(())
+    }
+}
@@ -25,28 +25,38 @@ use tracing_opentelemetry::OpenTelemetrySpanExt;
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
 use uuid::Uuid;
 
+mod analytics;
 mod config;
 mod db;
 mod error;
+mod events;
 mod payments;
+mod provider;
 mod reconciliation;
+mod retry;
 mod stripe;
 
+use analytics::{AnalyticsEvent, AnalyticsSink};
 use config::Config;
 use error::{AppError, Result};
+use events::{DomainEvent, EventBus, LocalEventBus, RedisEventBus};
 use payments::{
     validate_create_payment_intent, CreatePaymentIntentDto, CapturePaymentIntentDto,
     CreateRefundDto, PaymentIntentResponse, CaptureResponse, RefundResponse,
     FeeConfig, calculate_fees,
 };
-use stripe::{StripeClient, CreateRefundRequest};
+use provider::{CreatePaymentIntentParams, CreatePayoutParams, CreateRefundParams, PayoutConnector, PaymentProvider};
+use stripe::StripeClient;
 
 /// Application state shared across handlers.
 pub struct AppState {
     pub db: sqlx::PgPool,
-    pub stripe_client: StripeClient,
+    pub stripe_client: Box<dyn PaymentProvider>,
+    pub payout_connector: Box<dyn PayoutConnector>,
     pub config: Config,
     pub default_fee_config: FeeConfig,
+    pub event_bus: Box<dyn EventBus>,
+    pub analytics: AnalyticsSink,
 }
 
 #[derive(Clone)]
@@ -101,6 +111,13 @@ fn extract_parent_context(req: &ServiceRequest) -> OtelContext {
     global::get_text_map_propagator(|prop| prop.extract(&HeaderExtractor(req.headers())))
 }
 
+/// The trace id of the current tracing span, for stamping analytics events.
+fn current_trace_id() -> Option<String> {
+    let context = tracing::Span::current().context();
+    let span_context = context.span().span_context();
+    span_context.is_valid().then(|| span_context.trace_id().to_string())
+}
+
 fn build_tracer(default_service_name: &str) -> Option<sdktrace::Tracer> {
     let otel_enabled = env::var("OTEL_ENABLED").map(|value| value.to_lowercase() == "true").unwrap_or(false)
         || env::var("OTEL_EXPORTER_OTLP_ENDPOINT").is_ok();
@@ -175,6 +192,7 @@ async fn create_payment_intent(
     body: web::Json<CreatePaymentIntentDto>,
 ) -> Result<HttpResponse> {
     let dto = body.into_inner();
+    let started_at = std::time::Instant::now();
 
     // Validate the request
     validate_create_payment_intent(&dto)?;
@@ -200,35 +218,49 @@ async fn create_payment_intent(
         "platform_fee_cents": fee_calc.platform_fee_cents,
     });
 
-    // Create the payment intent
-    let request = stripe::CreatePaymentIntentRequest {
-        amount: fee_calc.charge_amount_cents as u64,
+    // Create the payment intent via whichever provider is configured
+    let params = CreatePaymentIntentParams {
+        amount_cents: fee_calc.charge_amount_cents as u64,
         currency: dto.currency.to_lowercase(),
-        customer: dto.customer_id,
-        payment_method: dto.payment_method_id,
-        payment_method_types: Some(vec!["card".to_string()]),
+        customer_id: dto.customer_id,
+        payment_method_id: dto.payment_method_id,
         capture_method: dto.capture_method,
-        confirm: None,
         description: dto.description,
         metadata: Some(metadata),
-        application_fee_amount: Some(fee_calc.application_fee_cents as u64),
-        transfer_data: Some(stripe::TransferDataRequest {
-            destination: stripe_account.clone(),
-        }),
-        on_behalf_of: Some(stripe_account),
+        application_fee_cents: Some(fee_calc.application_fee_cents as u64),
+        connected_account_id: Some(stripe_account),
     };
 
-    let intent = state
+    let result = state
         .stripe_client
-        .create_payment_intent(&request, None, dto.idempotency_key.as_deref())
-        .await?;
+        .create_payment_intent(params, dto.idempotency_key.as_deref())
+        .await;
+
+    state.analytics.record(AnalyticsEvent {
+        trace_id: current_trace_id(),
+        kind: "payment_intent.create".to_string(),
+        provider: Some("stripe".to_string()),
+        amount_cents: Some(fee_calc.charge_amount_cents as i64),
+        currency: Some(dto.currency.clone()),
+        platform_fee_cents: Some(fee_calc.platform_fee_cents as i64),
+        gateway_fee_cents: Some(fee_calc.gateway_fee_cents as i64),
+        connected_account_id: Some(dto.connected_account_id.clone()),
+        http_method: None,
+        http_route: None,
+        http_status: None,
+        latency_ms: Some(started_at.elapsed().as_millis() as u64),
+        outcome: if result.is_ok() { "success".to_string() } else { "error".to_string() },
+        metadata: serde_json::json!({ "reservation_id": dto.reservation_id }),
+    });
+
+    let session = result?;
 
     Ok(HttpResponse::Ok().json(PaymentIntentResponse {
-        id: intent.id,
-        client_secret: intent.client_secret.unwrap_or_default(),
-        status: format!("{:?}", intent.status),
-        amount_cents: intent.amount,
-        currency: intent.currency,
+        id: session.id().unwrap_or_default(),
+        client_secret: session.client_secret().unwrap_or_default(),
+        status: session.status(),
+        amount_cents: session.amount_cents(),
+        currency: session.currency(),
     }))
 }
 
@@ -240,12 +272,18 @@ async fn get_payment_intent(
 ) -> Result<HttpResponse> {
     let payment_intent_id = path.into_inner();
 
-    let intent = state
+    let session = state
         .stripe_client
         .get_payment_intent(&payment_intent_id, query.connected_account_id.as_deref())
         .await?;
 
-    Ok(HttpResponse::Ok().json(intent))
+    Ok(HttpResponse::Ok().json(PaymentIntentResponse {
+        id: session.id().unwrap_or_default(),
+        client_secret: session.client_secret().unwrap_or_default(),
+        status: session.status(),
+        amount_cents: session.amount_cents(),
+        currency: session.currency(),
+    }))
 }
 
 /// Capture a payment intent.
@@ -256,32 +294,53 @@ async fn capture_payment_intent(
 ) -> Result<HttpResponse> {
     let payment_intent_id = path.into_inner();
     let dto = body.into_inner();
+    let started_at = std::time::Instant::now();
 
-    let intent = state
+    let result = state
         .stripe_client
         .capture_payment_intent(
             &payment_intent_id,
             dto.amount_to_capture,
             dto.connected_account_id.as_deref(),
+            dto.idempotency_key.as_deref(),
         )
-        .await?;
+        .await;
+
+    state.analytics.record(AnalyticsEvent {
+        trace_id: current_trace_id(),
+        kind: "payment_intent.capture".to_string(),
+        provider: Some("stripe".to_string()),
+        amount_cents: None,
+        currency: None,
+        platform_fee_cents: None,
+        gateway_fee_cents: None,
+        connected_account_id: dto.connected_account_id.clone(),
+        http_method: None,
+        http_route: None,
+        http_status: None,
+        latency_ms: Some(started_at.elapsed().as_millis() as u64),
+        outcome: if result.is_ok() { "success".to_string() } else { "error".to_string() },
+        metadata: serde_json::json!({ "payment_intent_id": payment_intent_id }),
+    });
+
+    let session = result?;
 
     // Get receipt URL from the charge
-    let receipt_url = if let Some(charge_id) = &intent.latest_charge {
-        let charge = state
+    let receipt_url = if let Some(charge_id) = session.latest_charge() {
+        state
             .stripe_client
-            .get_charge(charge_id, dto.connected_account_id.as_deref())
+            .get_charge(&charge_id, dto.connected_account_id.as_deref())
             .await
-            .ok();
-        charge.and_then(|c| c.receipt_url)
+            .ok()
+            .and_then(|c| c.receipt_url)
     } else {
         None
     };
 
     Ok(HttpResponse::Ok().json(CaptureResponse {
-        id: intent.id,
-        status: format!("{:?}", intent.status),
-        amount_captured: intent.amount_received.unwrap_or(intent.amount),
+        id: session.id().unwrap_or_default(),
+        status: session.status(),
+        amount_captured: session.amount_received_cents().unwrap_or_else(|| session.amount_cents()),
         receipt_url,
     }))
 }
@@ -292,23 +351,44 @@ async fn create_refund(
     body: web::Json<CreateRefundDto>,
 ) -> Result<HttpResponse> {
     let dto = body.into_inner();
+    let started_at = std::time::Instant::now();
+    let payment_intent_id = dto.payment_intent_id.clone();
+    let connected_account_id = dto.connected_account_id.clone();
 
-    let request = CreateRefundRequest {
-        payment_intent: dto.payment_intent_id,
-        amount: dto.amount_cents,
+    let params = CreateRefundParams {
+        payment_intent_id: dto.payment_intent_id,
+        amount_cents: dto.amount_cents,
         reason: dto.reason,
-        metadata: None,
     };
 
-    let refund = state
+    let result = state
         .stripe_client
-        .create_refund(&request, dto.connected_account_id.as_deref(), dto.idempotency_key.as_deref())
-        .await?;
+        .create_refund(params, dto.connected_account_id.as_deref(), dto.idempotency_key.as_deref())
+        .await;
+
+    state.analytics.record(AnalyticsEvent {
+        trace_id: current_trace_id(),
+        kind: "refund.create".to_string(),
+        provider: Some("stripe".to_string()),
+        amount_cents: dto.amount_cents.map(|v| v as i64),
+        currency: None,
+        platform_fee_cents: None,
+        gateway_fee_cents: None,
+        connected_account_id,
+        http_method: None,
+        http_route: None,
+        http_status: None,
+        latency_ms: Some(started_at.elapsed().as_millis() as u64),
+        outcome: if result.is_ok() { "success".to_string() } else { "error".to_string() },
+        metadata: serde_json::json!({ "payment_intent_id": payment_intent_id }),
+    });
+
+    let refund = result?;
 
     Ok(HttpResponse::Ok().json(RefundResponse {
         id: refund.id,
         status: refund.status,
-        amount_cents: refund.amount,
+        amount_cents: refund.amount_cents,
     }))
 }
 
@@ -329,15 +409,8 @@ async fn handle_webhook(
         .and_then(|v| v.to_str().ok())
         .ok_or_else(|| AppError::Validation("Missing Stripe signature".to_string()))?;
 
-    // Verify signature
-    stripe::webhook::verify_webhook_signature(
-        &body,
-        signature,
-        &state.config.stripe_webhook_secret,
-    )?;
-
-    // Parse event
-    let event = stripe::webhook::parse_webhook_event(&body)?;
+    // Verify signature and decode into a provider-neutral event
+    let event = state.stripe_client.parse_webhook_event(&body, signature)?;
 
     tracing::info!(
         event_type = %event.event_type,
@@ -345,6 +418,17 @@ async fn handle_webhook(
         "Received webhook event"
     );
 
+    // Stripe delivers webhooks at-least-once; short-circuit on redelivery
+    // instead of reprocessing and re-publishing domain events. The event is
+    // only marked processed once its side effects below have actually
+    // succeeded, so a mid-request failure (DB blip, event bus down) leaves
+    // it unmarked and Stripe's retry will reprocess it instead of being
+    // silently dropped.
+    if db::is_event_processed(&state.db, &event.id).await? {
+        tracing::debug!(event_id = %event.id, "Webhook event already processed, skipping");
+        return Ok(HttpResponse::Ok().json(serde_json::json!({ "received": true })));
+    }
+
     // Handle event types
     match event.event_type.as_str() {
         "payment_intent.succeeded" => {
@@ -354,7 +438,24 @@ async fn handle_webhook(
                 amount = intent.amount,
                 "Payment succeeded"
             );
-            // TODO: Record payment in database if not already recorded
+            db::insert_or_update_payment(
+                &state.db,
+                &intent.id,
+                intent.latest_charge.as_deref(),
+                None,
+                "succeeded",
+                intent.amount as i64,
+                0,
+            )
+            .await?;
+            state
+                .event_bus
+                .publish(DomainEvent::PaymentSucceeded {
+                    payment_intent_id: intent.id,
+                    campground_id: None,
+                    amount_cents: intent.amount,
+                })
+                .await?;
         }
         "payment_intent.payment_failed" => {
             let intent: stripe::PaymentIntent = event.get_object()?;
@@ -362,7 +463,13 @@ async fn handle_webhook(
                 payment_intent_id = %intent.id,
                 "Payment failed"
             );
-            // TODO: Handle ACH returns
+            state
+                .event_bus
+                .publish(DomainEvent::PaymentFailed {
+                    payment_intent_id: intent.id,
+                    reason: None,
+                })
+                .await?;
         }
         "charge.refunded" => {
             let charge: stripe::Charge = event.get_object()?;
@@ -371,7 +478,25 @@ async fn handle_webhook(
                 amount_refunded = charge.amount_refunded,
                 "Charge refunded"
             );
-            // TODO: Record refund
+            if let Some(payment_intent_id) = &charge.payment_intent {
+                db::insert_or_update_payment(
+                    &state.db,
+                    payment_intent_id,
+                    Some(&charge.id),
+                    None,
+                    "refunded",
+                    charge.amount as i64,
+                    charge.amount_refunded as i64,
+                )
+                .await?;
+            }
+            state
+                .event_bus
+                .publish(DomainEvent::ChargeRefunded {
+                    charge_id: charge.id,
+                    amount_refunded_cents: charge.amount_refunded,
+                })
+                .await?;
         }
         "payout.paid" | "payout.updated" => {
             let payout: stripe::Payout = event.get_object()?;
@@ -380,7 +505,13 @@ async fn handle_webhook(
                 status = %payout.status,
                 "Payout updated"
             );
-            // TODO: Trigger reconciliation
+            state
+                .event_bus
+                .publish(DomainEvent::PayoutUpdated {
+                    payout_id: payout.id,
+                    status: payout.status,
+                })
+                .await?;
         }
         "charge.dispute.created" => {
             let dispute: stripe::Dispute = event.get_object()?;
@@ -390,13 +521,22 @@ async fn handle_webhook(
                 reason = %dispute.reason,
                 "Dispute created"
             );
-            // TODO: Handle dispute
+            state
+                .event_bus
+                .publish(DomainEvent::DisputeCreated {
+                    dispute_id: dispute.id,
+                    amount_cents: dispute.amount,
+                    reason: dispute.reason,
+                })
+                .await?;
         }
         _ => {
             tracing::debug!(event_type = %event.event_type, "Unhandled event type");
         }
     }
 
+    db::mark_event_processed(&state.db, &event.id, &event.event_type).await?;
+
     Ok(HttpResponse::Ok().json(serde_json::json!({ "received": true })))
 }
 
@@ -422,6 +562,64 @@ async fn calculate_fees_handler(
     Ok(HttpResponse::Ok().json(result))
 }
 
+// ============================================================================
+// Payout Initiation Handler
+// ============================================================================
+
+#[derive(Debug, serde::Deserialize)]
+struct CreatePayoutDto {
+    connected_account_id: String,
+    amount_cents: u64,
+    currency: String,
+    idempotency_key: String,
+}
+
+#[derive(Debug, serde::Serialize)]
+struct CreatePayoutResponse {
+    id: String,
+    status: String,
+    arrival_date: i64,
+}
+
+/// Initiate a payout to a connected account, as an outbound connector
+/// alongside the read-only reconciliation path below.
+async fn create_payout(
+    state: web::Data<Arc<AppState>>,
+    body: web::Json<CreatePayoutDto>,
+) -> Result<HttpResponse> {
+    let dto = body.into_inner();
+
+    let payout = state
+        .payout_connector
+        .create_payout(
+            CreatePayoutParams {
+                connected_account_id: dto.connected_account_id.clone(),
+                amount_cents: dto.amount_cents,
+                currency: dto.currency.clone(),
+            },
+            &dto.idempotency_key,
+        )
+        .await?;
+
+    db::insert_initiated_payout(
+        &state.db,
+        &payout.id,
+        &dto.connected_account_id,
+        dto.amount_cents as i64,
+        &dto.currency,
+        &payout.status,
+        Some(payout.arrival_date),
+        &dto.idempotency_key,
+    )
+    .await?;
+
+    Ok(HttpResponse::Ok().json(CreatePayoutResponse {
+        id: payout.id,
+        status: payout.status,
+        arrival_date: payout.arrival_date,
+    }))
+}
+
 // ============================================================================
 // Reconciliation Handlers
 // ============================================================================
@@ -438,7 +636,7 @@ async fn process_payout(
     state: web::Data<Arc<AppState>>,
     body: web::Json<ProcessPayoutRequest>,
 ) -> Result<HttpResponse> {
-    let service = reconciliation::PayoutReconciliationService::new(state.stripe_client.clone());
+    let service = reconciliation::PayoutReconciliationService::new(state.stripe_client.as_ref());
 
     let (record, entries) = service
         .process_payout(&body.payout_id, &body.campground_id, &body.stripe_account_id)
@@ -500,7 +698,14 @@ async fn compute_summary(
             "Drift detected in reconciliation"
         );
 
-        // TODO: Send alert to webhook
+        state
+            .event_bus
+            .publish(DomainEvent::DriftDetected {
+                payout_id: alert.payout_id.clone(),
+                drift_cents: alert.drift_cents,
+                severity: format!("{:?}", alert.severity),
+            })
+            .await?;
     }
 
     Ok(HttpResponse::Ok().json(serde_json::json!({
@@ -541,8 +746,15 @@ async fn main() -> std::io::Result<()> {
 
     tracing::info!("Connected to database");
 
-    // Create Stripe client
-    let stripe_client = StripeClient::new(config.stripe_secret_key.clone());
+    // Create the configured PSP. Stripe is the only provider today; a second
+    // PSP would be selected here from config and boxed the same way.
+    let stripe_for_payments = StripeClient::new(
+        config.stripe_secret_key.clone(),
+        config.stripe_webhook_secret.clone(),
+        &config,
+    );
+    let payout_connector: Box<dyn PayoutConnector> = Box::new(stripe_for_payments.clone());
+    let stripe_client: Box<dyn PaymentProvider> = Box::new(stripe_for_payments);
 
     // Create default fee config
     let default_fee_config = FeeConfig {
@@ -554,12 +766,29 @@ async fn main() -> std::io::Result<()> {
         gateway_fee_mode: payments::FeeMode::Absorb,
     };
 
+    // Select the event bus backend
+    let event_bus: Box<dyn EventBus> = match config.event_bus_backend.as_str() {
+        "redis" => {
+            let redis_url = config
+                .redis_url
+                .clone()
+                .expect("REDIS_URL is required when EVENT_BUS_BACKEND=redis");
+            Box::new(RedisEventBus::new(&redis_url, "keepr:payments:events").expect("Failed to create Redis event bus"))
+        }
+        _ => Box::new(LocalEventBus::new(1024)),
+    };
+
+    let analytics = AnalyticsSink::new(db.clone(), 4096, 100);
+
     // Create app state
     let state = Arc::new(AppState {
         db,
         stripe_client,
+        payout_connector,
         config: config.clone(),
         default_fee_config,
+        event_bus,
+        analytics,
     });
 
     let bind_addr = format!("{}:{}", config.host, config.port);
@@ -571,40 +800,68 @@ async fn main() -> std::io::Result<()> {
             .app_data(web::Data::new(state.clone()))
             .wrap(middleware::Logger::default())
             .wrap(middleware::Compress::default())
-            .wrap_fn(|req, srv| {
-                let context = build_request_context(&req);
-                let (trace_id, span_id) = parse_traceparent(context.traceparent.as_deref());
-                let tracestate_present = context.tracestate.is_some();
-                req.extensions_mut().insert(context.clone());
-                let span = tracing::info_span!(
-                    "http_request",
-                    request_id = %context.request_id,
-                    trace_id = field::Empty,
-                    span_id = field::Empty,
-                    tracestate_present = tracestate_present,
-                    method = %req.method(),
-                    path = %req.path()
-                );
-                let parent_context = extract_parent_context(&req);
-                if parent_context.span().span_context().is_valid() {
-                    span.set_parent(parent_context);
-                }
-                if let Some(value) = trace_id.as_deref() {
-                    span.record("trace_id", value);
-                }
-                if let Some(value) = span_id.as_deref() {
-                    span.record("span_id", value);
-                }
-                let fut = srv.call(req);
-                async move {
-                    let mut res = fut.await?;
-                    res.headers_mut().insert(
-                        HeaderName::from_static("x-request-id"),
-                        HeaderValue::from_str(&context.request_id).unwrap(),
+            .wrap_fn({
+                let analytics = state.analytics.clone();
+                move |req, srv| {
+                    let context = build_request_context(&req);
+                    let (trace_id, span_id) = parse_traceparent(context.traceparent.as_deref());
+                    let tracestate_present = context.tracestate.is_some();
+                    req.extensions_mut().insert(context.clone());
+                    let span = tracing::info_span!(
+                        "http_request",
+                        request_id = %context.request_id,
+                        trace_id = field::Empty,
+                        span_id = field::Empty,
+                        tracestate_present = tracestate_present,
+                        method = %req.method(),
+                        path = %req.path()
                     );
-                    Ok(res)
+                    let parent_context = extract_parent_context(&req);
+                    if parent_context.span().span_context().is_valid() {
+                        span.set_parent(parent_context);
+                    }
+                    if let Some(value) = trace_id.as_deref() {
+                        span.record("trace_id", value);
+                    }
+                    if let Some(value) = span_id.as_deref() {
+                        span.record("span_id", value);
+                    }
+                    let method = req.method().to_string();
+                    let path = req.path().to_string();
+                    let analytics = analytics.clone();
+                    let started_at = std::time::Instant::now();
+                    let fut = srv.call(req);
+                    async move {
+                        let result = fut.await;
+                        let (status, route) = match &result {
+                            Ok(res) => (Some(res.status().as_u16()), res.request().match_pattern()),
+                            Err(_) => (None, None),
+                        };
+                        analytics.record(AnalyticsEvent {
+                            trace_id: current_trace_id(),
+                            kind: "http_request".to_string(),
+                            provider: None,
+                            amount_cents: None,
+                            currency: None,
+                            platform_fee_cents: None,
+                            gateway_fee_cents: None,
+                            connected_account_id: None,
+                            http_method: Some(method),
+                            http_route: Some(route.unwrap_or(path)),
+                            http_status: status,
+                            latency_ms: Some(started_at.elapsed().as_millis() as u64),
+                            outcome: if result.is_ok() { "success".to_string() } else { "error".to_string() },
+                            metadata: serde_json::json!({}),
+                        });
+                        let mut res = result?;
+                        res.headers_mut().insert(
+                            HeaderName::from_static("x-request-id"),
+                            HeaderValue::from_str(&context.request_id).unwrap(),
+                        );
+                        Ok(res)
+                    }
+                    .instrument(span)
                 }
-                .instrument(span)
             })
             // Health check
             .route("/health", web::get().to(health))
@@ -618,6 +875,8 @@ async fn main() -> std::io::Result<()> {
             .route("/api/payments/calculate-fees", web::post().to(calculate_fees_handler))
             // Webhooks
             .route("/api/payments/webhook", web::post().to(handle_webhook))
+            // Payouts
+            .route("/api/payouts/create", web::post().to(create_payout))
             // Reconciliation
             .route("/api/reconciliation/process-payout", web::post().to(process_payout))
             .route("/api/reconciliation/compute-summary", web::post().to(compute_summary))
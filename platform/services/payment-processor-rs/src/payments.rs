@@ -0,0 +1,123 @@
+//! Payment intent DTOs and fee calculation.
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::{AppError, Result};
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum FeeMode {
+    /// The platform absorbs the fee out of its own cut.
+    Absorb,
+    /// The fee is passed on to the guest as a surcharge.
+    PassThrough,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct FeeConfig {
+    pub platform_fee_cents: u32,
+    pub platform_fee_percent: f64,
+    pub platform_fee_mode: FeeMode,
+    pub gateway_fee_percent: f64,
+    pub gateway_fee_cents: u32,
+    pub gateway_fee_mode: FeeMode,
+}
+
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct FeeCalculation {
+    pub base_amount_cents: u32,
+    pub platform_fee_cents: u32,
+    pub gateway_fee_cents: u32,
+    pub application_fee_cents: u32,
+    pub charge_amount_cents: u32,
+}
+
+/// Compute the platform/gateway fee split for a base amount.
+pub fn calculate_fees(amount_cents: u32, config: &FeeConfig) -> FeeCalculation {
+    let platform_fee_cents = config.platform_fee_cents
+        + ((amount_cents as f64) * (config.platform_fee_percent / 100.0)).round() as u32;
+    let gateway_fee_cents = config.gateway_fee_cents
+        + ((amount_cents as f64) * (config.gateway_fee_percent / 100.0)).round() as u32;
+
+    let mut charge_amount_cents = amount_cents;
+    if config.platform_fee_mode == FeeMode::PassThrough {
+        charge_amount_cents += platform_fee_cents;
+    }
+    if config.gateway_fee_mode == FeeMode::PassThrough {
+        charge_amount_cents += gateway_fee_cents;
+    }
+
+    FeeCalculation {
+        base_amount_cents: amount_cents,
+        platform_fee_cents,
+        gateway_fee_cents,
+        application_fee_cents: platform_fee_cents,
+        charge_amount_cents,
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CreatePaymentIntentDto {
+    pub campground_id: String,
+    pub connected_account_id: String,
+    pub reservation_id: String,
+    pub amount_cents: i64,
+    pub currency: String,
+    pub customer_id: Option<String>,
+    pub payment_method_id: Option<String>,
+    pub capture_method: Option<String>,
+    pub description: Option<String>,
+    pub idempotency_key: Option<String>,
+}
+
+pub fn validate_create_payment_intent(dto: &CreatePaymentIntentDto) -> Result<()> {
+    if dto.amount_cents <= 0 {
+        return Err(AppError::Validation(
+            "amount_cents must be positive".to_string(),
+        ));
+    }
+    if dto.currency.trim().is_empty() {
+        return Err(AppError::Validation("currency is required".to_string()));
+    }
+    Ok(())
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CapturePaymentIntentDto {
+    pub amount_to_capture: Option<u64>,
+    pub connected_account_id: Option<String>,
+    pub idempotency_key: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CreateRefundDto {
+    pub payment_intent_id: String,
+    pub amount_cents: Option<u64>,
+    pub reason: Option<String>,
+    pub connected_account_id: Option<String>,
+    pub idempotency_key: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct PaymentIntentResponse {
+    pub id: String,
+    pub client_secret: String,
+    pub status: String,
+    pub amount_cents: u64,
+    pub currency: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CaptureResponse {
+    pub id: String,
+    pub status: String,
+    pub amount_captured: u64,
+    pub receipt_url: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RefundResponse {
+    pub id: String,
+    pub status: String,
+    pub amount_cents: u64,
+}
@@ -0,0 +1,156 @@
+//! Provider-agnostic payment abstraction.
+//!
+//! Handlers talk to a `Box<dyn PaymentProvider>` rather than a concrete
+//! Stripe client so Campreserv can add a second PSP without touching route
+//! handlers. Provider-specific session state (client secrets, connected-account
+//! routing, idempotency cursors) stays behind `PaymentSessionData` and is never
+//! exposed to the handler layer directly.
+
+use async_trait::async_trait;
+use serde::Serialize;
+
+use crate::error::Result;
+
+/// Opaque, provider-defined payment-intent/session state. Handlers only ever
+/// read it through these accessors, never by downcasting to a concrete type.
+pub trait PaymentSessionData: Send + Sync {
+    fn id(&self) -> Option<String>;
+    fn client_secret(&self) -> Option<String>;
+    fn status(&self) -> String;
+    fn amount_cents(&self) -> u64;
+    fn currency(&self) -> String;
+    fn latest_charge(&self) -> Option<String>;
+    fn amount_received_cents(&self) -> Option<u64>;
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ChargeData {
+    pub id: String,
+    pub amount_refunded_cents: u64,
+    pub receipt_url: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RefundData {
+    pub id: String,
+    pub status: String,
+    pub amount_cents: u64,
+}
+
+#[derive(Debug, Clone)]
+pub struct CreatePaymentIntentParams {
+    pub amount_cents: u64,
+    pub currency: String,
+    pub customer_id: Option<String>,
+    pub payment_method_id: Option<String>,
+    pub capture_method: Option<String>,
+    pub description: Option<String>,
+    pub metadata: Option<serde_json::Value>,
+    pub application_fee_cents: Option<u64>,
+    pub connected_account_id: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct CreateRefundParams {
+    pub payment_intent_id: String,
+    pub amount_cents: Option<u64>,
+    pub reason: Option<String>,
+}
+
+/// A provider-neutral view of a webhook delivery, already signature-verified.
+pub struct ProviderWebhookEvent {
+    pub id: String,
+    pub event_type: String,
+    object: serde_json::Value,
+}
+
+impl ProviderWebhookEvent {
+    pub fn new(id: String, event_type: String, object: serde_json::Value) -> Self {
+        Self {
+            id,
+            event_type,
+            object,
+        }
+    }
+
+    pub fn get_object<T: serde::de::DeserializeOwned>(&self) -> Result<T> {
+        serde_json::from_value(self.object.clone())
+            .map_err(|e| crate::error::AppError::Validation(format!("invalid webhook object: {e}")))
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct CreatePayoutParams {
+    pub connected_account_id: String,
+    pub amount_cents: u64,
+    pub currency: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PayoutData {
+    pub id: String,
+    pub status: String,
+    pub amount_cents: u64,
+    pub arrival_date: i64,
+}
+
+/// A connector capable of *initiating* outbound payouts, as opposed to only
+/// reconciling payouts the PSP already made on its own schedule.
+#[async_trait]
+pub trait PayoutConnector: Send + Sync {
+    async fn create_payout(
+        &self,
+        params: CreatePayoutParams,
+        idempotency_key: &str,
+    ) -> Result<PayoutData>;
+}
+
+/// A payment service provider. `StripeClient` is the only implementation
+/// today; a second PSP is added by implementing this trait and selecting it
+/// in `AppState` from config.
+#[async_trait]
+pub trait PaymentProvider: Send + Sync {
+    async fn create_payment_intent(
+        &self,
+        params: CreatePaymentIntentParams,
+        idempotency_key: Option<&str>,
+    ) -> Result<Box<dyn PaymentSessionData>>;
+
+    async fn get_payment_intent(
+        &self,
+        payment_intent_id: &str,
+        connected_account_id: Option<&str>,
+    ) -> Result<Box<dyn PaymentSessionData>>;
+
+    async fn capture_payment_intent(
+        &self,
+        payment_intent_id: &str,
+        amount_to_capture: Option<u64>,
+        connected_account_id: Option<&str>,
+        idempotency_key: Option<&str>,
+    ) -> Result<Box<dyn PaymentSessionData>>;
+
+    async fn get_charge(
+        &self,
+        charge_id: &str,
+        connected_account_id: Option<&str>,
+    ) -> Result<ChargeData>;
+
+    /// Fetch a payout the PSP already made on its own schedule (as opposed
+    /// to `PayoutConnector::create_payout`, which initiates one).
+    async fn get_payout(
+        &self,
+        payout_id: &str,
+        connected_account_id: Option<&str>,
+    ) -> Result<PayoutData>;
+
+    async fn create_refund(
+        &self,
+        params: CreateRefundParams,
+        connected_account_id: Option<&str>,
+        idempotency_key: Option<&str>,
+    ) -> Result<RefundData>;
+
+    /// Verify and decode a webhook delivery into a provider-neutral event.
+    fn parse_webhook_event(&self, payload: &[u8], signature: &str) -> Result<ProviderWebhookEvent>;
+}
@@ -0,0 +1,152 @@
+//! Payout reconciliation: comparing Stripe payout amounts against our own
+//! ledger of payments, refunds, fees, and chargebacks.
+
+use serde::Serialize;
+
+use crate::error::Result;
+use crate::provider::PaymentProvider;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct LedgerEntry {
+    pub payout_id: String,
+    pub campground_id: String,
+    pub account: String,
+    pub amount_cents: i64,
+    pub entry_type: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct LedgerEntryPair {
+    pub debit: LedgerEntry,
+    pub credit: LedgerEntry,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PayoutRecord {
+    pub payout_id: String,
+    pub campground_id: String,
+    pub status: String,
+    pub amount_cents: u64,
+}
+
+/// Talks to whichever `PaymentProvider` the caller hands it, so reconciling
+/// a payout doesn't tie the handler layer to a concrete Stripe client.
+pub struct PayoutReconciliationService<'a> {
+    provider: &'a dyn PaymentProvider,
+}
+
+impl<'a> PayoutReconciliationService<'a> {
+    pub fn new(provider: &'a dyn PaymentProvider) -> Self {
+        Self { provider }
+    }
+
+    /// Fetch a payout from the PSP and turn it into a ledger debit/credit pair.
+    pub async fn process_payout(
+        &self,
+        payout_id: &str,
+        campground_id: &str,
+        stripe_account_id: &str,
+    ) -> Result<(PayoutRecord, Vec<LedgerEntryPair>)> {
+        let payout = self.provider.get_payout(payout_id, Some(stripe_account_id)).await?;
+
+        let record = PayoutRecord {
+            payout_id: payout_id.to_string(),
+            campground_id: campground_id.to_string(),
+            status: payout.status,
+            amount_cents: payout.amount_cents,
+        };
+        let amount_cents = payout.amount_cents;
+
+        let entries = vec![LedgerEntryPair {
+            debit: LedgerEntry {
+                payout_id: payout_id.to_string(),
+                campground_id: campground_id.to_string(),
+                account: "stripe_payouts".to_string(),
+                amount_cents: amount_cents as i64,
+                entry_type: "debit".to_string(),
+            },
+            credit: LedgerEntry {
+                payout_id: payout_id.to_string(),
+                campground_id: campground_id.to_string(),
+                account: "campground_balance".to_string(),
+                amount_cents: amount_cents as i64,
+                entry_type: "credit".to_string(),
+            },
+        }];
+
+        Ok((record, entries))
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ReconciliationSummary {
+    pub payout_id: String,
+    pub campground_id: String,
+    pub stripe_amount_cents: i64,
+    pub expected_amount_cents: i64,
+    pub drift_cents: i64,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum DriftSeverity {
+    Warning,
+    Critical,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DriftAlert {
+    pub payout_id: String,
+    pub drift_cents: i64,
+    pub severity: DriftSeverity,
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn compute_reconciliation_summary(
+    payout_id: &str,
+    campground_id: &str,
+    stripe_amount_cents: i64,
+    payments_cents: i64,
+    refunds_cents: i64,
+    stripe_fees_cents: i64,
+    platform_fees_cents: i64,
+    chargebacks_cents: i64,
+    _drift_threshold_cents: i64,
+) -> ReconciliationSummary {
+    let expected_amount_cents = payments_cents
+        - refunds_cents
+        - stripe_fees_cents
+        - platform_fees_cents
+        - chargebacks_cents;
+
+    ReconciliationSummary {
+        payout_id: payout_id.to_string(),
+        campground_id: campground_id.to_string(),
+        stripe_amount_cents,
+        expected_amount_cents,
+        drift_cents: stripe_amount_cents - expected_amount_cents,
+    }
+}
+
+pub fn create_drift_alert(
+    summary: &ReconciliationSummary,
+    warning_threshold_cents: i64,
+    critical_threshold_cents: i64,
+) -> Option<DriftAlert> {
+    let drift = summary.drift_cents.abs();
+    if drift >= critical_threshold_cents {
+        Some(DriftAlert {
+            payout_id: summary.payout_id.clone(),
+            drift_cents: summary.drift_cents,
+            severity: DriftSeverity::Critical,
+        })
+    } else if drift >= warning_threshold_cents {
+        Some(DriftAlert {
+            payout_id: summary.payout_id.clone(),
+            drift_cents: summary.drift_cents,
+            severity: DriftSeverity::Warning,
+        })
+    } else {
+        None
+    }
+}
@@ -0,0 +1,77 @@
+//! Exponential backoff with jitter for outbound provider calls.
+//!
+//! Only idempotent calls (plain reads, or writes carrying an idempotency key)
+//! should ever retry a transient failure — callers opt in explicitly rather
+//! than this module guessing from the HTTP method.
+
+use rand::Rng;
+
+use crate::config::Config;
+use crate::error::{AppError, Result};
+
+#[derive(Debug, Clone, Copy)]
+pub struct RetryPolicy {
+    pub max_attempts: u32,
+    pub base_delay_ms: u64,
+}
+
+impl RetryPolicy {
+    pub fn from_config(config: &Config) -> Self {
+        Self {
+            max_attempts: config.stripe_max_retry_attempts,
+            base_delay_ms: config.stripe_retry_base_delay_ms,
+        }
+    }
+
+    fn delay_for_attempt(&self, attempt: u32, retry_after: Option<u64>) -> std::time::Duration {
+        if let Some(seconds) = retry_after {
+            return std::time::Duration::from_secs(seconds);
+        }
+        let exp_ms = self.base_delay_ms.saturating_mul(1u64 << attempt.min(16));
+        let jitter_ms = rand::thread_rng().gen_range(0..=self.base_delay_ms.max(1));
+        std::time::Duration::from_millis(exp_ms + jitter_ms)
+    }
+}
+
+/// Run `operation` up to `policy.max_attempts` times in total (the first
+/// attempt included), retrying on transient (429/5xx) failures, honoring
+/// Stripe's `Retry-After` header and backing
+/// off exponentially with jitter otherwise. A final failure is logged with
+/// the attempt count and last status before being returned to the caller.
+pub async fn with_retry<T, F, Fut>(policy: RetryPolicy, operation_name: &str, mut operation: F) -> Result<T>
+where
+    F: FnMut() -> Fut,
+    Fut: std::future::Future<Output = Result<T>>,
+{
+    let mut attempt = 0u32;
+    loop {
+        attempt += 1;
+        match operation().await {
+            Ok(value) => return Ok(value),
+            Err(AppError::StripeTransient { status, retry_after, ref message }) if attempt < policy.max_attempts => {
+                let delay = policy.delay_for_attempt(attempt, retry_after);
+                tracing::warn!(
+                    operation = operation_name,
+                    attempt,
+                    status,
+                    retry_after_secs = retry_after,
+                    delay_ms = delay.as_millis() as u64,
+                    message,
+                    "retrying transient provider failure"
+                );
+                tokio::time::sleep(delay).await;
+            }
+            Err(AppError::StripeTransient { status, message, .. }) => {
+                tracing::error!(
+                    operation = operation_name,
+                    attempts = attempt,
+                    status,
+                    message,
+                    "provider call failed after exhausting retries"
+                );
+                return Err(AppError::Stripe(message));
+            }
+            Err(other) => return Err(other),
+        }
+    }
+}
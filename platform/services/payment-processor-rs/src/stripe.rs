@@ -0,0 +1,540 @@
+//! Thin client over the Stripe REST API, and the `PaymentProvider` impl that
+//! exposes it to the handler layer as one PSP among possibly several.
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+
+use crate::config::Config;
+use crate::error::{AppError, Result};
+use crate::provider::{
+    ChargeData, CreatePaymentIntentParams, CreatePayoutParams, CreateRefundParams, PayoutConnector,
+    PayoutData, PaymentProvider, PaymentSessionData, ProviderWebhookEvent, RefundData,
+};
+use crate::retry::{with_retry, RetryPolicy};
+
+const STRIPE_API_BASE: &str = "https://api.stripe.com/v1";
+
+#[derive(Clone)]
+pub struct StripeClient {
+    secret_key: String,
+    webhook_secret: String,
+    http: reqwest::Client,
+    retry_policy: RetryPolicy,
+}
+
+impl StripeClient {
+    pub fn new(secret_key: String, webhook_secret: String, config: &Config) -> Self {
+        Self {
+            secret_key,
+            webhook_secret,
+            http: reqwest::Client::new(),
+            retry_policy: RetryPolicy::from_config(config),
+        }
+    }
+
+    /// Only calls that are naturally idempotent (GETs) or that carry an
+    /// idempotency key may retry; everything else gets exactly one attempt.
+    async fn maybe_retry<T, F, Fut>(&self, name: &str, retryable: bool, operation: F) -> Result<T>
+    where
+        F: FnMut() -> Fut,
+        Fut: std::future::Future<Output = Result<T>>,
+    {
+        if retryable {
+            with_retry(self.retry_policy, name, operation).await
+        } else {
+            let mut operation = operation;
+            operation().await
+        }
+    }
+
+    pub async fn create_payment_intent(
+        &self,
+        request: &CreatePaymentIntentRequest,
+        _connected_account_id: Option<&str>,
+        idempotency_key: Option<&str>,
+    ) -> Result<PaymentIntent> {
+        self.maybe_retry("create_payment_intent", idempotency_key.is_some(), || async {
+            let mut req = self
+                .http
+                .post(format!("{STRIPE_API_BASE}/payment_intents"))
+                .basic_auth(&self.secret_key, Some(""))
+                .form(request);
+            if let Some(key) = idempotency_key {
+                req = req.header("Idempotency-Key", key);
+            }
+            let response = req
+                .send()
+                .await
+                .map_err(|e| AppError::Stripe(e.to_string()))?;
+            parse_stripe_response(response).await
+        })
+        .await
+    }
+
+    pub async fn get_payment_intent(
+        &self,
+        payment_intent_id: &str,
+        _connected_account_id: Option<&str>,
+    ) -> Result<PaymentIntent> {
+        self.maybe_retry("get_payment_intent", true, || async {
+            let response = self
+                .http
+                .get(format!("{STRIPE_API_BASE}/payment_intents/{payment_intent_id}"))
+                .basic_auth(&self.secret_key, Some(""))
+                .send()
+                .await
+                .map_err(|e| AppError::Stripe(e.to_string()))?;
+            parse_stripe_response(response).await
+        })
+        .await
+    }
+
+    pub async fn capture_payment_intent(
+        &self,
+        payment_intent_id: &str,
+        amount_to_capture: Option<u64>,
+        _connected_account_id: Option<&str>,
+        idempotency_key: Option<&str>,
+    ) -> Result<PaymentIntent> {
+        self.maybe_retry("capture_payment_intent", idempotency_key.is_some(), || async {
+            let mut form = Vec::new();
+            if let Some(amount) = amount_to_capture {
+                form.push(("amount_to_capture", amount.to_string()));
+            }
+            let mut req = self
+                .http
+                .post(format!(
+                    "{STRIPE_API_BASE}/payment_intents/{payment_intent_id}/capture"
+                ))
+                .basic_auth(&self.secret_key, Some(""))
+                .form(&form);
+            if let Some(key) = idempotency_key {
+                req = req.header("Idempotency-Key", key);
+            }
+            let response = req.send().await.map_err(|e| AppError::Stripe(e.to_string()))?;
+            parse_stripe_response(response).await
+        })
+        .await
+    }
+
+    pub async fn get_charge(&self, charge_id: &str, _connected_account_id: Option<&str>) -> Result<Charge> {
+        self.maybe_retry("get_charge", true, || async {
+            let response = self
+                .http
+                .get(format!("{STRIPE_API_BASE}/charges/{charge_id}"))
+                .basic_auth(&self.secret_key, Some(""))
+                .send()
+                .await
+                .map_err(|e| AppError::Stripe(e.to_string()))?;
+            parse_stripe_response(response).await
+        })
+        .await
+    }
+
+    pub async fn create_payout(
+        &self,
+        request: &CreatePayoutRequest,
+        connected_account_id: &str,
+        idempotency_key: &str,
+    ) -> Result<Payout> {
+        self.maybe_retry("create_payout", true, || async {
+            let response = self
+                .http
+                .post(format!("{STRIPE_API_BASE}/payouts"))
+                .basic_auth(&self.secret_key, Some(""))
+                .header("Stripe-Account", connected_account_id)
+                .header("Idempotency-Key", idempotency_key)
+                .form(request)
+                .send()
+                .await
+                .map_err(|e| AppError::Stripe(e.to_string()))?;
+            parse_stripe_response(response).await
+        })
+        .await
+    }
+
+    pub async fn get_payout(&self, payout_id: &str, connected_account_id: Option<&str>) -> Result<Payout> {
+        self.maybe_retry("get_payout", true, || async {
+            let mut req = self
+                .http
+                .get(format!("{STRIPE_API_BASE}/payouts/{payout_id}"))
+                .basic_auth(&self.secret_key, Some(""));
+            if let Some(account) = connected_account_id {
+                req = req.header("Stripe-Account", account);
+            }
+            let response = req.send().await.map_err(|e| AppError::Stripe(e.to_string()))?;
+            parse_stripe_response(response).await
+        })
+        .await
+    }
+
+    pub async fn create_refund(
+        &self,
+        request: &CreateRefundRequest,
+        _connected_account_id: Option<&str>,
+        idempotency_key: Option<&str>,
+    ) -> Result<Refund> {
+        self.maybe_retry("create_refund", idempotency_key.is_some(), || async {
+            let mut req = self
+                .http
+                .post(format!("{STRIPE_API_BASE}/refunds"))
+                .basic_auth(&self.secret_key, Some(""))
+                .form(request);
+            if let Some(key) = idempotency_key {
+                req = req.header("Idempotency-Key", key);
+            }
+            let response = req
+                .send()
+                .await
+                .map_err(|e| AppError::Stripe(e.to_string()))?;
+            parse_stripe_response(response).await
+        })
+        .await
+    }
+}
+
+async fn parse_stripe_response<T: serde::de::DeserializeOwned>(
+    response: reqwest::Response,
+) -> Result<T> {
+    if !response.status().is_success() {
+        let status = response.status();
+        let retry_after = response
+            .headers()
+            .get("retry-after")
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.parse::<u64>().ok());
+        let body = response.text().await.unwrap_or_default();
+        let message = format!("stripe returned {status}: {body}");
+
+        return if status.as_u16() == 429 || status.is_server_error() {
+            Err(AppError::StripeTransient {
+                status: status.as_u16(),
+                retry_after,
+                message,
+            })
+        } else {
+            Err(AppError::Stripe(message))
+        };
+    }
+    response
+        .json::<T>()
+        .await
+        .map_err(|e| AppError::Stripe(e.to_string()))
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TransferDataRequest {
+    pub destination: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CreatePaymentIntentRequest {
+    pub amount: u64,
+    pub currency: String,
+    pub customer: Option<String>,
+    pub payment_method: Option<String>,
+    pub payment_method_types: Option<Vec<String>>,
+    pub capture_method: Option<String>,
+    pub confirm: Option<bool>,
+    pub description: Option<String>,
+    pub metadata: Option<serde_json::Value>,
+    pub application_fee_amount: Option<u64>,
+    pub transfer_data: Option<TransferDataRequest>,
+    pub on_behalf_of: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CreatePayoutRequest {
+    pub amount: u64,
+    pub currency: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CreateRefundRequest {
+    pub payment_intent: String,
+    pub amount: Option<u64>,
+    pub reason: Option<String>,
+    pub metadata: Option<serde_json::Value>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum PaymentIntentStatus {
+    RequiresPaymentMethod,
+    RequiresConfirmation,
+    RequiresAction,
+    Processing,
+    RequiresCapture,
+    Canceled,
+    Succeeded,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PaymentIntent {
+    pub id: String,
+    pub client_secret: Option<String>,
+    pub status: PaymentIntentStatus,
+    pub amount: u64,
+    pub amount_received: Option<u64>,
+    pub currency: String,
+    pub latest_charge: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Charge {
+    pub id: String,
+    pub payment_intent: Option<String>,
+    pub amount: u64,
+    pub amount_refunded: u64,
+    pub receipt_url: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Refund {
+    pub id: String,
+    pub status: String,
+    pub amount: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Payout {
+    pub id: String,
+    pub status: String,
+    pub amount: u64,
+    pub arrival_date: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Dispute {
+    pub id: String,
+    pub amount: u64,
+    pub reason: String,
+}
+
+/// Wraps a Stripe `PaymentIntent` so handlers only see it through
+/// `PaymentSessionData`, never as a Stripe-shaped struct.
+pub struct StripeSessionData(pub PaymentIntent);
+
+impl PaymentSessionData for StripeSessionData {
+    fn id(&self) -> Option<String> {
+        Some(self.0.id.clone())
+    }
+
+    fn client_secret(&self) -> Option<String> {
+        self.0.client_secret.clone()
+    }
+
+    fn status(&self) -> String {
+        format!("{:?}", self.0.status)
+    }
+
+    fn amount_cents(&self) -> u64 {
+        self.0.amount
+    }
+
+    fn currency(&self) -> String {
+        self.0.currency.clone()
+    }
+
+    fn latest_charge(&self) -> Option<String> {
+        self.0.latest_charge.clone()
+    }
+
+    fn amount_received_cents(&self) -> Option<u64> {
+        self.0.amount_received
+    }
+}
+
+#[async_trait]
+impl PaymentProvider for StripeClient {
+    async fn create_payment_intent(
+        &self,
+        params: CreatePaymentIntentParams,
+        idempotency_key: Option<&str>,
+    ) -> Result<Box<dyn PaymentSessionData>> {
+        let request = CreatePaymentIntentRequest {
+            amount: params.amount_cents,
+            currency: params.currency,
+            customer: params.customer_id,
+            payment_method: params.payment_method_id,
+            payment_method_types: Some(vec!["card".to_string()]),
+            capture_method: params.capture_method,
+            confirm: None,
+            description: params.description,
+            metadata: params.metadata,
+            application_fee_amount: params.application_fee_cents,
+            transfer_data: params.connected_account_id.clone().map(|destination| TransferDataRequest { destination }),
+            on_behalf_of: params.connected_account_id,
+        };
+        let intent = StripeClient::create_payment_intent(self, &request, None, idempotency_key).await?;
+        Ok(Box::new(StripeSessionData(intent)))
+    }
+
+    async fn get_payment_intent(
+        &self,
+        payment_intent_id: &str,
+        connected_account_id: Option<&str>,
+    ) -> Result<Box<dyn PaymentSessionData>> {
+        let intent = StripeClient::get_payment_intent(self, payment_intent_id, connected_account_id).await?;
+        Ok(Box::new(StripeSessionData(intent)))
+    }
+
+    async fn capture_payment_intent(
+        &self,
+        payment_intent_id: &str,
+        amount_to_capture: Option<u64>,
+        connected_account_id: Option<&str>,
+        idempotency_key: Option<&str>,
+    ) -> Result<Box<dyn PaymentSessionData>> {
+        let intent = StripeClient::capture_payment_intent(
+            self,
+            payment_intent_id,
+            amount_to_capture,
+            connected_account_id,
+            idempotency_key,
+        )
+        .await?;
+        Ok(Box::new(StripeSessionData(intent)))
+    }
+
+    async fn get_charge(&self, charge_id: &str, connected_account_id: Option<&str>) -> Result<ChargeData> {
+        let charge = StripeClient::get_charge(self, charge_id, connected_account_id).await?;
+        Ok(ChargeData {
+            id: charge.id,
+            amount_refunded_cents: charge.amount_refunded,
+            receipt_url: charge.receipt_url,
+        })
+    }
+
+    async fn get_payout(&self, payout_id: &str, connected_account_id: Option<&str>) -> Result<PayoutData> {
+        let payout = StripeClient::get_payout(self, payout_id, connected_account_id).await?;
+        Ok(PayoutData {
+            id: payout.id,
+            status: payout.status,
+            amount_cents: payout.amount,
+            arrival_date: payout.arrival_date,
+        })
+    }
+
+    async fn create_refund(
+        &self,
+        params: CreateRefundParams,
+        connected_account_id: Option<&str>,
+        idempotency_key: Option<&str>,
+    ) -> Result<RefundData> {
+        let request = CreateRefundRequest {
+            payment_intent: params.payment_intent_id,
+            amount: params.amount_cents,
+            reason: params.reason,
+            metadata: None,
+        };
+        let refund = StripeClient::create_refund(self, &request, connected_account_id, idempotency_key).await?;
+        Ok(RefundData {
+            id: refund.id,
+            status: refund.status,
+            amount_cents: refund.amount,
+        })
+    }
+
+    fn parse_webhook_event(&self, payload: &[u8], signature: &str) -> Result<ProviderWebhookEvent> {
+        webhook::verify_webhook_signature(payload, signature, &self.webhook_secret)?;
+        let event = webhook::parse_webhook_event(payload)?;
+        Ok(ProviderWebhookEvent::new(
+            event.id,
+            event.event_type,
+            event.data.object,
+        ))
+    }
+}
+
+#[async_trait]
+impl PayoutConnector for StripeClient {
+    async fn create_payout(
+        &self,
+        params: CreatePayoutParams,
+        idempotency_key: &str,
+    ) -> Result<PayoutData> {
+        let request = CreatePayoutRequest {
+            amount: params.amount_cents,
+            currency: params.currency,
+        };
+        let payout = StripeClient::create_payout(self, &request, &params.connected_account_id, idempotency_key).await?;
+        Ok(PayoutData {
+            id: payout.id,
+            status: payout.status,
+            amount_cents: payout.amount,
+            arrival_date: payout.arrival_date,
+        })
+    }
+}
+
+pub mod webhook {
+    use hmac::{Hmac, Mac};
+    use sha2::Sha256;
+
+    use super::Result;
+    use crate::error::AppError;
+
+    #[derive(Debug, serde::Deserialize)]
+    pub struct WebhookEvent {
+        pub id: String,
+        #[serde(rename = "type")]
+        pub event_type: String,
+        pub data: WebhookEventData,
+    }
+
+    #[derive(Debug, serde::Deserialize)]
+    pub struct WebhookEventData {
+        pub object: serde_json::Value,
+    }
+
+    impl WebhookEvent {
+        pub fn get_object<T: serde::de::DeserializeOwned>(&self) -> Result<T> {
+            serde_json::from_value(self.data.object.clone())
+                .map_err(|e| AppError::Stripe(format!("failed to decode event object: {e}")))
+        }
+    }
+
+    /// Verify the `Stripe-Signature` header against the raw request body.
+    pub fn verify_webhook_signature(payload: &[u8], signature_header: &str, secret: &str) -> Result<()> {
+        let mut timestamp = None;
+        let mut signatures = Vec::new();
+        for part in signature_header.split(',') {
+            let mut kv = part.splitn(2, '=');
+            match (kv.next(), kv.next()) {
+                (Some("t"), Some(v)) => timestamp = Some(v),
+                (Some("v1"), Some(v)) => signatures.push(v),
+                _ => {}
+            }
+        }
+        let timestamp = timestamp
+            .ok_or_else(|| AppError::Validation("missing webhook timestamp".to_string()))?;
+
+        let signed_payload = format!("{timestamp}.{}", String::from_utf8_lossy(payload));
+        let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes())
+            .map_err(|e| AppError::Internal(e.to_string()))?;
+        mac.update(signed_payload.as_bytes());
+
+        // `Mac::verify_slice` compares the tag in constant time; a plain
+        // `==` on the hex-encoded strings would let an attacker recover the
+        // expected signature one byte at a time via response timing.
+        let verified = signatures.iter().any(|sig| {
+            hex::decode(sig)
+                .map(|decoded| mac.clone().verify_slice(&decoded).is_ok())
+                .unwrap_or(false)
+        });
+
+        if verified {
+            Ok(())
+        } else {
+            Err(AppError::Unauthorized(
+                "webhook signature verification failed".to_string(),
+            ))
+        }
+    }
+
+    pub fn parse_webhook_event(payload: &[u8]) -> Result<WebhookEvent> {
+        serde_json::from_slice(payload)
+            .map_err(|e| AppError::Validation(format!("invalid webhook payload: {e}")))
+    }
+}